@@ -137,6 +137,49 @@ fn whttp_parse_http1<'incoming>(req: &mut std::pin::Pin<&mut whttp::Request>, in
     r
 }
 
+/// `whttp_parse_http1` by `parse::Scanner`, the vectorized one `http1::load` uses
+#[inline(always)]
+fn whttp_parse_http1_scanner<'incoming>(req: &mut std::pin::Pin<&mut whttp::Request>, incoming: &'incoming [u8]) -> whttp::request::parse::Scanner<'incoming> {
+    use whttp::{request::parse, header};
+
+    parse::clear(req);
+    {
+        let size = usize::min(incoming.len(), parse::BUF_SIZE);
+        parse::buf(req.as_mut())[..size].copy_from_slice(&incoming[..size]);
+    }
+
+    let mut r = parse::Scanner::new(unsafe {
+        let buf = parse::buf(req.as_mut());
+        std::slice::from_raw_parts(buf.as_ptr(), buf.len())
+    });
+
+    unsafe {parse::method(req, r.read_until(b' '))}.unwrap();
+    r.consume(" ").unwrap();
+
+    unsafe {parse::path(req, r.read_until(b' '))}.unwrap();
+    r.consume(" ").unwrap();
+
+    r.consume("HTTP/1.1\r\n");
+
+    while r.consume("\r\n").is_none() {
+        let name_bytes = r.read_until(b':');
+        r.consume(": ").unwrap();
+        let value_bytes = r.read_until(b'\r');
+        unsafe {parse::header(req, name_bytes, value_bytes)}.unwrap();
+        r.consume("\r\n");
+    }
+
+    if let Some(n @ 1..) = req
+        .header(header::ContentLength)
+        .map(|v| v.bytes().fold(0, |n, b| 10*n + (b-b'0') as usize))
+    {
+        assert_eq!(r.index + n, incoming.len());
+        parse::body_own(req, Vec::from(&incoming[r.index..(r.index + n)]));
+    }
+
+    r
+}
+
 #[bench] fn parse_http1_large_httpcrate(b: &mut test::Bencher) {
     let incoming = incoming_http1_large();
     b.iter(|| -> ::http::Request<Vec<u8>> {httpcrate_parse_http1(&incoming)});
@@ -158,3 +201,16 @@ fn whttp_parse_http1<'incoming>(req: &mut std::pin::Pin<&mut whttp::Request>, in
     let mut req = std::pin::Pin::new(&mut req);
     b.iter(|| -> ::byte_reader::Reader {whttp_parse_http1(&mut req, &incoming)});
 }
+
+#[bench] fn parse_http1_large_whttp_scanner(b: &mut test::Bencher) {
+    let incoming = incoming_http1_large();
+    let mut req = whttp::request::parse::new();
+    let mut req = std::pin::Pin::new(&mut req);
+    b.iter(|| -> whttp::request::parse::Scanner {whttp_parse_http1_scanner(&mut req, &incoming)});
+}
+#[bench] fn parse_http1_small_whttp_scanner(b: &mut test::Bencher) {
+    let incoming = incoming_http1_small();
+    let mut req = whttp::request::parse::new();
+    let mut req = std::pin::Pin::new(&mut req);
+    b.iter(|| -> whttp::request::parse::Scanner {whttp_parse_http1_scanner(&mut req, &incoming)});
+}
@@ -43,6 +43,18 @@ fn incoming_http1_small() -> Vec<u8> {
     "))
 }
 
+/// `incoming_http1_large` with the header names recased by `recase`,
+/// like ones from clients or proxies not using the canonical casing
+fn incoming_http1_large_recased(recase: fn(&mut [u8])) -> Vec<u8> {
+    let mut incoming = incoming_http1_large();
+    let mut line_start = 0;
+    while let Some(colon) = incoming[line_start..].iter().position(|&b| b == b':') {
+        recase(&mut incoming[line_start..line_start + colon]);
+        line_start += incoming[line_start..].windows(2).position(|w| w == b"\r\n").unwrap() + 2;
+    }
+    black_box(incoming)
+}
+
 #[bench] fn parse_http1_headers_large_httpcrate(b: &mut test::Bencher) {
     use ::http::{HeaderMap, HeaderName, HeaderValue};
 
@@ -89,6 +101,98 @@ fn incoming_http1_small() -> Vec<u8> {
     });
 }
 
+#[bench] fn parse_http1_headers_large_lowercase_httpcrate(b: &mut test::Bencher) {
+    use ::http::{HeaderMap, HeaderName, HeaderValue};
+
+    let incoming = incoming_http1_large_recased(<[u8]>::make_ascii_lowercase);
+
+    b.iter(|| -> HeaderMap {
+        let mut h = HeaderMap::new();
+        let mut r = ::byte_reader::Reader::new(&incoming);
+        while r.consume("\r\n").is_none() {
+            let key_bytes = r.read_while(|&b| b != b':');
+            r.consume(": ").unwrap();
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key_bytes),
+                HeaderValue::from_bytes(r.read_while(|&b| b != b'\r'))
+            ) {
+                h.append(name, value);
+            }
+            r.consume("\r\n");
+        }
+        h
+    });
+}
+
+#[bench] fn parse_http1_headers_large_lowercase_whttp(b: &mut test::Bencher) {
+    use whttp::{Headers, Header, Value};
+
+    let incoming = incoming_http1_large_recased(<[u8]>::make_ascii_lowercase);
+
+    b.iter(|| -> Headers {
+        let mut h = Headers::new();
+        let mut r = ::byte_reader::Reader::new(&incoming);
+        while r.consume("\r\n").is_none() {
+            let key_bytes = r.read_while(|&b| b != b':');
+            r.consume(": ").unwrap();
+            if let (Ok(name), Ok(value)) = (
+                unsafe {Header::parse_mainly_standard(key_bytes)},
+                unsafe {Value::parse(r.read_while(|&b| b != b'\r'))}
+            ) {
+                h.append(&name, value);
+            }
+            r.consume("\r\n");
+        }
+        h
+    });
+}
+
+#[bench] fn parse_http1_headers_large_uppercase_httpcrate(b: &mut test::Bencher) {
+    use ::http::{HeaderMap, HeaderName, HeaderValue};
+
+    let incoming = incoming_http1_large_recased(<[u8]>::make_ascii_uppercase);
+
+    b.iter(|| -> HeaderMap {
+        let mut h = HeaderMap::new();
+        let mut r = ::byte_reader::Reader::new(&incoming);
+        while r.consume("\r\n").is_none() {
+            let key_bytes = r.read_while(|&b| b != b':');
+            r.consume(": ").unwrap();
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(key_bytes),
+                HeaderValue::from_bytes(r.read_while(|&b| b != b'\r'))
+            ) {
+                h.append(name, value);
+            }
+            r.consume("\r\n");
+        }
+        h
+    });
+}
+
+#[bench] fn parse_http1_headers_large_uppercase_whttp(b: &mut test::Bencher) {
+    use whttp::{Headers, Header, Value};
+
+    let incoming = incoming_http1_large_recased(<[u8]>::make_ascii_uppercase);
+
+    b.iter(|| -> Headers {
+        let mut h = Headers::new();
+        let mut r = ::byte_reader::Reader::new(&incoming);
+        while r.consume("\r\n").is_none() {
+            let key_bytes = r.read_while(|&b| b != b':');
+            r.consume(": ").unwrap();
+            if let (Ok(name), Ok(value)) = (
+                unsafe {Header::parse_mainly_standard(key_bytes)},
+                unsafe {Value::parse(r.read_while(|&b| b != b'\r'))}
+            ) {
+                h.append(&name, value);
+            }
+            r.consume("\r\n");
+        }
+        h
+    });
+}
+
 #[bench] fn parse_http1_headers_small_httpcrate(b: &mut test::Bencher) {
     use ::http::{HeaderMap, HeaderName, HeaderValue};
 
@@ -1,6 +1,6 @@
 use whttp::{Request, Response, http1};
-use whttp::header::{ContentType, Date};
-use whttp::util::IMFfixdate;
+use whttp::header::Date;
+use whttp::util::{IMFfixdate, mime::{self, Mime}};
 
 #[tokio::main]
 async fn main() -> std::io::Result<()> {
@@ -36,8 +36,8 @@ async fn main() -> std::io::Result<()> {
 }
 
 async fn handle(req: &Request) -> Response {
-    if !(req.header(ContentType)
-            .is_some_and(|ct| ct.starts_with("text/plain"))
+    if !(req.headers().typed_get::<Mime>()
+            .is_some_and(|ct| ct.matches(&mime::TEXT_PLAIN))
     ) {
         return Response::BadRequest()
             .with(Date, IMFfixdate::now())
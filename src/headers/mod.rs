@@ -3,14 +3,22 @@ mod name;
 mod value;
 
 pub mod util;
+pub mod typed;
 
 pub use name::{Header, standard};
 pub use value::Value;
+pub use typed::TypedHeader;
 
 use ::hashbrown::hash_table::{HashTable, Entry};
 
+/// HTTP headers, iterated in insertion order.
+/// 
+/// Lookup is done in O(1) by pre-calculated hash of `Header` via a swiss table
+/// of indices to insertion-ordered entries. Removal is O(n) to keep the order,
+/// where n is the number of headers ( usually small ).
 pub struct Headers {
-    table: HashTable<(Header, Value)>
+    table:   HashTable<usize>,
+    entries: Vec<(Header, Value)>,
 }
 
 const _/* trait impls */: () = {
@@ -28,8 +36,12 @@ const _/* trait impls */: () = {
         }
     }
 
+    /// order-insensitive
     impl PartialEq for Headers {
         fn eq(&self, other: &Self) -> bool {
+            if self.len() != other.len() {
+                return false
+            }
             for (h, v) in self.iter() {
                 if other.get(h) != Some(v) {
                     return false
@@ -49,46 +61,79 @@ const _/* trait impls */: () = {
     }
 };
 
+/// `PartialEq` of `Header`: the pre-calculated hash first, and then
+/// the name only when hashes are the same
 #[inline(always)]
-const fn hasher((h, _): &(Header, Value)) -> u64 {
-    h.hash
+fn eq_to<'h>(header: &'h Header, entries: &'h [(Header, Value)]) -> impl Fn(&usize) -> bool + 'h {
+    move |&i| entries[i].0 == *header
 }
 
 #[inline(always)]
-const fn eq_to(header: &Header) -> impl Fn(&(Header, Value)) -> bool + '_ {
-    |(h, _)| h.hash == header.hash
+fn hasher(entries: &[(Header, Value)]) -> impl Fn(&usize) -> u64 + '_ {
+    move |&i| entries[i].0.hash
 }
 
 impl Headers {
     pub const fn new() -> Self {
-        Self { table: HashTable::new() }
+        Self { table: HashTable::new(), entries: Vec::new() }
     }
 
     pub fn with_capacity(capacity: usize) -> Self {
-        Self { table: HashTable::with_capacity(capacity) }
+        Self { table: HashTable::with_capacity(capacity), entries: Vec::with_capacity(capacity) }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
     }
 
     #[inline]
     pub fn get(&self, header: &Header) -> Option<&str> {
-        match self.table.find(header.hash, eq_to(header)) {
-            Some((_, v)) => Some(&*v),
+        match self.table.find(header.hash, eq_to(header, &self.entries)) {
+            Some(&i) => Some(&*self.entries[i].1),
             None => None
         }
     }
 
+    /// `None` when the header is missing or its value is invalid as `T`
+    #[inline]
+    pub fn typed_get<T: TypedHeader>(&self) -> Option<T> {
+        match self.table.find(T::HEADER.hash, eq_to(T::HEADER, &self.entries)) {
+            Some(&i) => T::decode(&self.entries[i].1),
+            None => None
+        }
+    }
+
+    #[inline]
+    pub fn typed_set<T: TypedHeader>(&mut self, typed: T) -> &mut Self {
+        self.insert(T::HEADER, typed.encode());
+        self
+    }
+
     #[inline]
     pub fn insert(&mut self, header: &Header, value: impl Into<Value>) {
         let value = value.into();
-        match self.table.entry(header.hash, eq_to(header), hasher) {
-            Entry::Occupied(mut entry) => {entry.get_mut().1 = value;}
-            Entry::Vacant(entry) => {entry.insert((header.clone(), value));}
+        let Self { table, entries } = self;
+        match table.entry(header.hash, eq_to(header, entries), hasher(entries)) {
+            Entry::Occupied(entry) => {entries[*entry.get()].1 = value;}
+            Entry::Vacant(entry) => {entry.insert(entries.len()); entries.push((*header, value));}
         }
     }
 
     #[inline]
     pub fn remove(&mut self, header: &Header) -> Option<Value> {
-        if let Ok(entry) = self.table.find_entry(header.hash, eq_to(header)) {
-            Some(entry.remove().0.1)
+        let Self { table, entries } = self;
+        if let Ok(entry) = table.find_entry(header.hash, eq_to(header, entries)) {
+            let (removed, _) = entry.remove();
+            for i in table.iter_mut() {
+                if *i > removed {*i -= 1}
+            }
+            Some(entries.remove(removed).1)
         } else {
             None
         }
@@ -96,26 +141,23 @@ impl Headers {
 
     #[inline]
     pub fn clear(&mut self) {
-        self.table.clear()
+        self.table.clear();
+        self.entries.clear();
     }
 
     #[inline]
     pub fn append(&mut self, header: &Header, value: impl Into<Value>) -> &mut Self {
-        let value = value.into();
-        match self.table.entry(header.hash, eq_to(header), hasher) {
-            Entry::Occupied(mut entry) => {entry.get_mut().1.append(value);}
-            Entry::Vacant(entry) => {entry.insert((header.clone(), value));}
-        }
-        self
+        self.push(*header, value)
     }
 
     /// append with owned `Header` (mainly used in request parsing)
     #[inline]
     pub fn push(&mut self, header: Header, value: impl Into<Value>) -> &mut Self {
         let value = value.into();
-        match self.table.entry(header.hash, eq_to(&header), hasher) {
-            Entry::Occupied(mut entry) => {entry.get_mut().1.append(value);}
-            Entry::Vacant(entry) => {entry.insert((header, value));}
+        let Self { table, entries } = self;
+        match table.entry(header.hash, eq_to(&header, entries), hasher(entries)) {
+            Entry::Occupied(entry) => {entries[*entry.get()].1.append(value);}
+            Entry::Vacant(entry) => {entry.insert(entries.len()); entries.push((header, value));}
         }
         self
     }
@@ -126,9 +168,10 @@ impl Headers {
         self
     }
 
+    /// iterate headers in insertion order
     #[inline]
     pub fn iter(&self) -> impl Iterator<Item = (&Header, &str)> {
-        self.table.iter().map(|(h, v)| (h, &**v))
+        self.entries.iter().map(|(h, v)| (h, &**v))
     }
 }
 
@@ -149,3 +192,81 @@ const _: () = {
         }
     }
 };
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use standard::{Authorization, ContentLength};
+
+    #[test]
+    fn test_colliding_headers_are_not_confused() {
+        let evil_authorization = Header::forged("X-Evil-Authorization", Authorization.hash);
+        let evil_content_length = Header::forged("X-Evil-Content-Length", ContentLength.hash);
+
+        let mut h = Headers::new();
+        h.insert(Authorization, "Bearer token");
+        h.insert(ContentLength, "42");
+
+        h.insert(&evil_authorization, "Bearer spoofed");
+        h.append(&evil_content_length, "0");
+        assert_eq!(h.get(Authorization), Some("Bearer token"));
+        assert_eq!(h.get(ContentLength), Some("42"));
+        assert_eq!(h.get(&evil_authorization), Some("Bearer spoofed"));
+        assert_eq!(h.get(&evil_content_length), Some("0"));
+
+        assert_eq!(h.remove(&evil_authorization).as_deref(), Some("Bearer spoofed"));
+        assert_eq!(h.get(Authorization), Some("Bearer token"));
+        assert_eq!(h.get(&evil_authorization), None);
+
+        let mut h2 = Headers::new();
+        h2.insert(Authorization, "Bearer token");
+        h2.insert(ContentLength, "42");
+        h2.insert(&evil_content_length, "0");
+        assert_eq!(h, h2);
+        h2.remove(ContentLength);
+        h2.insert(&Header::forged("X-Evil-Content-Length", ContentLength.hash), "42");
+        assert_ne!(h, h2);
+    }
+
+    #[test]
+    fn test_insertion_order() {
+        use standard::{ContentType, Date, Vary};
+
+        const X_CUSTOM: &Header = &Header::def("X-Custom");
+
+        let mut h = Headers::new();
+        h.insert(X_CUSTOM, "1");
+        h.insert(Vary, "Origin");
+        h.insert(ContentType, "text/plain");
+        h.insert(Date, "Sun, 06 Nov 1994 08:49:37 GMT");
+        h.insert(Vary, "Accept");
+        h.append(X_CUSTOM, "2");
+        assert_eq!(h.iter().map(|(h, v)| (&**h, v)).collect::<Vec<_>>(), [
+            ("X-Custom", "1,2"),
+            ("Vary", "Accept"),
+            ("Content-Type", "text/plain"),
+            ("Date", "Sun, 06 Nov 1994 08:49:37 GMT"),
+        ]);
+
+        h.remove(Vary);
+        h.insert(Vary, "Origin");
+        assert_eq!(h.iter().map(|(h, _)| &**h).collect::<Vec<_>>(), [
+            "X-Custom", "Content-Type", "Date", "Vary"
+        ]);
+        assert_eq!(h.get(ContentType), Some("text/plain"));
+        assert_eq!(h.get(Date), Some("Sun, 06 Nov 1994 08:49:37 GMT"));
+        assert_eq!(h.get(X_CUSTOM), Some("1,2"));
+        assert_eq!(h.len(), 4);
+    }
+
+    #[test]
+    fn test_case_insensitive_lookup() {
+        let mut h = Headers::new();
+        h.insert(&Header::def("x-custom"), "value");
+        assert_eq!(h.get(&Header::def("X-Custom")), Some("value"));
+        assert_eq!(h.get(&Header::def("X-CUSTOM")), Some("value"));
+    }
+}
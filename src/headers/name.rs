@@ -46,9 +46,16 @@ const _/* trait impls */: () = {
     }
     
     impl PartialEq for Header {
+        /// Compares the pre-calculated hashes first, and then the names
+        /// case-insensitively unless they're the same reference (like
+        /// the same `header::*` const), so that headers whose hashes collide
+        /// are never confused.
         #[inline]
         fn eq(&self, other: &Self) -> bool {
-            self.hash == other.hash
+            self.hash == other.hash && (
+                std::ptr::addr_eq(self.name.as_ptr(), other.name.as_ptr()) ||
+                self.eq_ignore_ascii_case(other)
+            )
         }
     }
     impl Eq for Header {}
@@ -116,11 +123,27 @@ impl Header {
     /// Parse header name to `Header` with better performance when
     /// `name` is usually a name of standard header.
     /// 
+    /// Standard headers are recognized in any casing. The original casing
+    /// of `name` is preserved even when it's spelled differently from
+    /// `header::*` (like `content-type` or `CONTENT-TYPE`), sharing the
+    /// pre-calculated hash of the standard one.
+    /// 
     /// SAFETY: `name` is valid reference whenever the return value can be accessed
     #[inline(always)]
     pub unsafe fn parse_mainly_standard(name: &[u8]) -> Result<Self, InvalidHeader> {
         match Standard::from_bytes(name) {
-            Some(s) => Ok(*s.as_header()),
+            Some(s) => {
+                let standard = s.as_header();
+                if name == standard.as_bytes() {
+                    Ok(*standard)
+                } else {
+                    // SAFETY: `name` matched a standard header, so it's valid UTF-8
+                    let name = unsafe {std::str::from_utf8_unchecked(name)};
+                    // SAFETY: function SAFETY
+                    let name = unsafe {NonNull::new_unchecked(name as *const str as *mut str)};
+                    Ok(Header { name, hash: standard.hash })
+                }
+            }
             None => Self::parse(name)
         }
     }
@@ -141,8 +164,38 @@ impl Header {
     }
 }
 
+impl Header {
+    /// The spelling of this header in canonical casing: the one of
+    /// `header::*` for standard headers (like `ETag`, `WWW-Authenticate`),
+    /// and Title-Case (like `X-Custom-Header`) for others.
+    pub fn canonical(&self) -> std::borrow::Cow<'_, str> {
+        use std::borrow::Cow;
+
+        let name = &**self;
+        if let Some(s) = Standard::from_bytes(name.as_bytes()) {
+            return Cow::Borrowed(&**s.as_header())
+        }
+
+        if name.split('-').all(|word| {
+            let mut bytes = word.bytes();
+            bytes.next().is_none_or(|first| !first.is_ascii_lowercase()) &&
+            bytes.all(|b| !b.is_ascii_uppercase())
+        }) {
+            return Cow::Borrowed(name)
+        }
+
+        let mut canonical = String::with_capacity(name.len());
+        let mut title = true;
+        for c in name.chars() {
+            canonical.push(if title {c.to_ascii_uppercase()} else {c.to_ascii_lowercase()});
+            title = c == '-';
+        }
+        Cow::Owned(canonical)
+    }
+}
+
 macro_rules! Standard {
-    ($( $name:ident = $bytes:literal | $lower:literal )*) => {
+    ($( $name:ident = $bytes:literal )*) => {
         pub mod standard {
             use super::*;
             $(
@@ -151,17 +204,14 @@ macro_rules! Standard {
             )*
         }
 
+        #[derive(Clone, Copy)]
         enum Standard {
             $( $name, )*
         }
         impl Standard {
-            #[inline(always)]
-            const fn from_bytes(bytes: &[u8]) -> Option<Self> {
-                match bytes {
-                    $( $bytes | $lower => Some(Standard::$name) ,)*
-                    _ => None
-                }
-            }
+            const ALL: &[(Self, &[u8])] = &[
+                $( (Self::$name, $bytes), )*
+            ];
 
             #[inline(always)]
             const fn as_header(&self) -> &'static Header {
@@ -173,91 +223,232 @@ macro_rules! Standard {
     };
 }
 Standard! {
-    Accept                          = b"Accept" | b"accept"
-    AcceptCH                        = b"Accept-CH" | b"accept-ch"
-    AcceptEncoding                  = b"Accept-Encoding" | b"accept-encoding"
-    AcceptLanguage                  = b"Accept-Language" | b"accept-language"
-    AcceptPatch                     = b"Accept-Patch" | b"accept-patch"
-    AcceptPost                      = b"Accept-Post" | b"accept-post"
-    AcceptRanges                    = b"Accept-Ranges" | b"accept-ranges"
-    AccessControlAllowCredentials   = b"Access-Control-Allow-Credentials" | b"access-control-allow-credentials"
-    AccessControlAllowHeaders       = b"Access-Control-Allow-Headers" | b"access-control-allow-headers"
-    AccessControlAllowMethods       = b"Access-Control-Allow-Methods" | b"access-control-allow-methods"
-    AccessControlAllowOrigin        = b"Access-Control-Allow-Origin" | b"access-control-allow-origin"
-    AccessControlExposeHeaders      = b"Access-Control-Expose-Headers" | b"access-control-expose-headers"
-    AccessControlMaxAge             = b"Access-Control-Max-Age" | b"access-control-max-age"
-    AccessControlRequestHeaders     = b"Access-Control-Request-Headers" | b"access-control-request-headers"
-    AccessControlRequestMethod      = b"Access-Control-Request-Method" | b"access-control-request-method"
-    Age                             = b"Age" | b"age"
-    Allow                           = b"Allow" | b"allow"
-    AltSvc                          = b"Alt-Svc" | b"alt-svc"
-    AltUsed                         = b"Alt-Used" | b"alt-used"
-    Authorization                   = b"Authorization" | b"authorization"
-    CacheControl                    = b"Cache-Control" | b"cache-control"
-    ClearSiteData                   = b"Clear-Site-Data" | b"clear-site-data"
-    Connection                      = b"Connection" | b"connection"
-    ContentDisposition              = b"Content-Disposition" | b"content-disposition"
-    ContentEcoding                  = b"Content-Ecoding" | b"content-ecoding"
-    ContentLanguage                 = b"Content-Language" | b"content-language"
-    ContentLength                   = b"Content-Length" | b"content-length"
-    ContentLocation                 = b"Content-Location" | b"content-location"
-    ContentRange                    = b"Content-Range" | b"content-range"
-    ContentSecurityPolicy           = b"Content-Security-Policy" | b"content-security-policy"
-    ContentSecurityPolicyReportOnly = b"Content-Security-Policy-Report-Only" | b"content-security-policy-report-only"
-    ContentType                     = b"Content-Type" | b"content-type"
-    Cookie                          = b"Cookie" | b"cookie"
-    CrossOriginEmbedderPolicy       = b"Cross-Origin-Embedder-Policy" | b"cross-origin-embedder-policy"
-    CrossOriginOpenerPolicy         = b"Cross-Origin-Opener-Policy" | b"cross-origin-opener-policy"
-    CrossOriginResourcePolicy       = b"Cross-Origin-Resource-Policy" | b"cross-origin-resource-policy"
-    Date                            = b"Date" | b"date"
-    DeviceMemory                    = b"Device-Memory" | b"device-memory"
-    ETag                            = b"ETag" | b"etag"
-    Expect                          = b"Expect" | b"expect"
-    Expires                         = b"Expires" | b"expires"
-    Forwarded                       = b"Forwarded" | b"forwarded"
-    From                            = b"From" | b"from"
-    Host                            = b"Host" | b"host"
-    IfMatch                         = b"If-Match" | b"if-match"
-    IfModifiedSince                 = b"If-Modified-Since" | b"if-modified-since"
-    IfNoneMatch                     = b"If-None-Match" | b"if-none-match"
-    IfRange                         = b"If-Range" | b"if-range"
-    IfUnmodifiedSince               = b"If-Unmodified-Since" | b"if-unmodified-since"
-    KeepAlive                       = b"Keep-Alive" | b"keep-alive"
-    LastModified                    = b"Last-Modified" | b"last-modified"
-    Link                            = b"Link" | b"link"
-    Location                        = b"Location" | b"location"
-    MaxForwards                     = b"Max-Forwards" | b"max-forwards"
-    Origin                          = b"Origin" | b"origin"
-    Priority                        = b"Priority" | b"priority"
-    ProxyAuthenticate               = b"Proxy-Authenticate" | b"proxy-authenticate"
-    ProxyAuthorization              = b"Proxy-Authorization" | b"proxy-authorization"
-    Range                           = b"Range" | b"range"
-    Referer                         = b"Referer" | b"referer"
-    ReferrerPolicy                  = b"Referrer-Policy" | b"referrer-policy"
-    Refresh                         = b"Refresh" | b"refresh"
-    RetryAfter                      = b"Retry-After" | b"retry-after"
-    SecFetchDest                    = b"Sec-Fetch-Dest" | b"sec-fetch-dest"
-    SecFetchMode                    = b"Sec-Fetch-Mode" | b"sec-fetch-mode"
-    SecFetchSite                    = b"Sec-Fetch-Site" | b"sec-fetch-site"
-    SecFetchUser                    = b"Sec-Fetch-User" | b"sec-fetch-user"
-    SecWebSocketAccept              = b"Sec-WebSocket-Accept" | b"sec-websocket-accept"
-    SecWebSocketExtensions          = b"Sec-WebSocket-Extensions" | b"sec-websocket-extensions"
-    SecWebSocketKey                 = b"Sec-WebSocket-Key" | b"sec-websocket-key"
-    SecWebSocketProtocol            = b"Sec-WebSocket-Protocol" | b"sec-websocket-protocol"
-    SecWebSocketVersion             = b"Sec-WebSocket-Version" | b"sec-websocket-version"
-    Server                          = b"Server" | b"server"
-    SetCookie                       = b"Set-Cookie" | b"set-cookie"
-    StrictTransportSecurity         = b"Strict-Transport-Security" | b"strict-transport-security"
-    TE                              = b"TE" | b"te"
-    TimingAllowOrigin               = b"Timing-Allow-Origin" | b"timing-allow-origin"
-    Trailer                         = b"Trailer" | b"trailer"
-    TransferEncoding                = b"Transfer-Encoding" | b"transfer-encoding"
-    Upgrade                         = b"Upgrade" | b"upgrade"
-    UpgradeInsecureRequests         = b"Upgrade-Insecure-Requests" | b"upgrade-insecure-requests"
-    UserAgent                       = b"User-Agent" | b"user-agent"
-    Vary                            = b"Vary" | b"vary"
-    Via                             = b"Via" | b"via"
-    WWWAuthenticate                 = b"WWW-Authenticate" | b"www-authenticate"
-    XContentTypeOptions             = b"X-Content-Type-Options" | b"x-content-type-options"
-    XFrameOptions                   = b"X-Frame-Options" | b"x-frame-options"
+    Accept                          = b"Accept"
+    AcceptCH                        = b"Accept-CH"
+    AcceptEncoding                  = b"Accept-Encoding"
+    AcceptLanguage                  = b"Accept-Language"
+    AcceptPatch                     = b"Accept-Patch"
+    AcceptPost                      = b"Accept-Post"
+    AcceptRanges                    = b"Accept-Ranges"
+    AccessControlAllowCredentials   = b"Access-Control-Allow-Credentials"
+    AccessControlAllowHeaders       = b"Access-Control-Allow-Headers"
+    AccessControlAllowMethods       = b"Access-Control-Allow-Methods"
+    AccessControlAllowOrigin        = b"Access-Control-Allow-Origin"
+    AccessControlExposeHeaders      = b"Access-Control-Expose-Headers"
+    AccessControlMaxAge             = b"Access-Control-Max-Age"
+    AccessControlRequestHeaders     = b"Access-Control-Request-Headers"
+    AccessControlRequestMethod      = b"Access-Control-Request-Method"
+    Age                             = b"Age"
+    Allow                           = b"Allow"
+    AltSvc                          = b"Alt-Svc"
+    AltUsed                         = b"Alt-Used"
+    Authorization                   = b"Authorization"
+    CacheControl                    = b"Cache-Control"
+    ClearSiteData                   = b"Clear-Site-Data"
+    Connection                      = b"Connection"
+    ContentDisposition              = b"Content-Disposition"
+    ContentEcoding                  = b"Content-Ecoding"
+    ContentLanguage                 = b"Content-Language"
+    ContentLength                   = b"Content-Length"
+    ContentLocation                 = b"Content-Location"
+    ContentRange                    = b"Content-Range"
+    ContentSecurityPolicy           = b"Content-Security-Policy"
+    ContentSecurityPolicyReportOnly = b"Content-Security-Policy-Report-Only"
+    ContentType                     = b"Content-Type"
+    Cookie                          = b"Cookie"
+    CrossOriginEmbedderPolicy       = b"Cross-Origin-Embedder-Policy"
+    CrossOriginOpenerPolicy         = b"Cross-Origin-Opener-Policy"
+    CrossOriginResourcePolicy       = b"Cross-Origin-Resource-Policy"
+    Date                            = b"Date"
+    DeviceMemory                    = b"Device-Memory"
+    ETag                            = b"ETag"
+    Expect                          = b"Expect"
+    Expires                         = b"Expires"
+    Forwarded                       = b"Forwarded"
+    From                            = b"From"
+    Host                            = b"Host"
+    IfMatch                         = b"If-Match"
+    IfModifiedSince                 = b"If-Modified-Since"
+    IfNoneMatch                     = b"If-None-Match"
+    IfRange                         = b"If-Range"
+    IfUnmodifiedSince               = b"If-Unmodified-Since"
+    KeepAlive                       = b"Keep-Alive"
+    LastModified                    = b"Last-Modified"
+    Link                            = b"Link"
+    Location                        = b"Location"
+    MaxForwards                     = b"Max-Forwards"
+    Origin                          = b"Origin"
+    Priority                        = b"Priority"
+    ProxyAuthenticate               = b"Proxy-Authenticate"
+    ProxyAuthorization              = b"Proxy-Authorization"
+    Range                           = b"Range"
+    Referer                         = b"Referer"
+    ReferrerPolicy                  = b"Referrer-Policy"
+    Refresh                         = b"Refresh"
+    RetryAfter                      = b"Retry-After"
+    SecFetchDest                    = b"Sec-Fetch-Dest"
+    SecFetchMode                    = b"Sec-Fetch-Mode"
+    SecFetchSite                    = b"Sec-Fetch-Site"
+    SecFetchUser                    = b"Sec-Fetch-User"
+    SecWebSocketAccept              = b"Sec-WebSocket-Accept"
+    SecWebSocketExtensions          = b"Sec-WebSocket-Extensions"
+    SecWebSocketKey                 = b"Sec-WebSocket-Key"
+    SecWebSocketProtocol            = b"Sec-WebSocket-Protocol"
+    SecWebSocketVersion             = b"Sec-WebSocket-Version"
+    Server                          = b"Server"
+    SetCookie                       = b"Set-Cookie"
+    StrictTransportSecurity         = b"Strict-Transport-Security"
+    TE                              = b"TE"
+    TimingAllowOrigin               = b"Timing-Allow-Origin"
+    Trailer                         = b"Trailer"
+    TransferEncoding                = b"Transfer-Encoding"
+    Upgrade                         = b"Upgrade"
+    UpgradeInsecureRequests         = b"Upgrade-Insecure-Requests"
+    UserAgent                       = b"User-Agent"
+    Vary                            = b"Vary"
+    Via                             = b"Via"
+    WWWAuthenticate                 = b"WWW-Authenticate"
+    XContentTypeOptions             = b"X-Content-Type-Options"
+    XForwardedFor                   = b"X-Forwarded-For"
+    XForwardedHost                  = b"X-Forwarded-Host"
+    XForwardedProto                 = b"X-Forwarded-Proto"
+    XFrameOptions                   = b"X-Frame-Options"
+}
+
+/// Case-insensitive perfect hash of the standard header names, keyed by
+/// the length and the first, middle and last two bytes folded by `| 0x20`.
+/// The seed is searched at compile time, so a new standard header never
+/// makes a collision silently.
+impl Standard {
+    const TABLE_BITS: u32 = 9;
+
+    const MIN_LEN: usize = {
+        let (mut min, mut i) = (usize::MAX, 0);
+        while i < Self::ALL.len() {
+            if Self::ALL[i].1.len() < min {min = Self::ALL[i].1.len()}
+            i += 1;
+        }
+        min
+    };
+    const MAX_LEN: usize = {
+        let (mut max, mut i) = (0, 0);
+        while i < Self::ALL.len() {
+            if Self::ALL[i].1.len() > max {max = Self::ALL[i].1.len()}
+            i += 1;
+        }
+        max
+    };
+
+    const SEED: u32 = {
+        let mut seed = 0;
+        'search: loop {
+            if seed == 1 << 16 {panic!("no perfect hash seed for the standard headers")}
+
+            let mut used = [false; 1 << Self::TABLE_BITS];
+            let mut i = 0;
+            while i < Self::ALL.len() {
+                let slot = Self::slot(Self::ALL[i].1, seed);
+                if used[slot] {seed += 1; continue 'search}
+                used[slot] = true;
+                i += 1;
+            }
+            break seed
+        }
+    };
+
+    /// index in `ALL` for each slot, `u8::MAX` for empty ones
+    const TABLE: [u8; 1 << Self::TABLE_BITS] = {
+        let mut table = [u8::MAX; 1 << Self::TABLE_BITS];
+        let mut i = 0;
+        while i < Self::ALL.len() {
+            table[Self::slot(Self::ALL[i].1, Self::SEED)] = i as u8;
+            i += 1;
+        }
+        table
+    };
+
+    /// `name` must be `MIN_LEN..=MAX_LEN` long
+    #[inline(always)]
+    const fn slot(name: &[u8], seed: u32) -> usize {
+        #[inline(always)]
+        const fn mix(hash: u32, byte: u8) -> u32 {
+            (hash ^ byte as u32).wrapping_mul(0x0100_0193)
+        }
+
+        let len = name.len();
+        let hash = mix(seed, len as u8);
+        let hash = mix(hash, name[0] | 0x20);
+        let hash = mix(hash, name[len / 2] | 0x20);
+        let hash = mix(hash, name[len - 2] | 0x20);
+        let hash = mix(hash, name[len - 1] | 0x20);
+        (hash >> (32 - Self::TABLE_BITS)) as usize
+    }
+
+    /// standard header of `bytes` in any casing
+    #[inline(always)]
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        if !(Self::MIN_LEN..=Self::MAX_LEN).contains(&bytes.len()) {return None}
+        let (standard, name) = *Self::ALL.get(Self::TABLE[Self::slot(bytes, Self::SEED)] as usize)?;
+        bytes.eq_ignore_ascii_case(name).then_some(standard)
+    }
+}
+
+#[cfg(test)]
+impl Header {
+    /// `Header` of `name` with arbitrary `hash`, to emulate a hash collision
+    pub(crate) const fn forged(name: &'static str, hash: u64) -> Self {
+        let name = unsafe {NonNull::new_unchecked(name as *const str as *mut str)};
+        Header { name, hash }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_eq_with_colliding_hash() {
+        let evil = Header::forged("X-Evil", standard::Authorization.hash);
+        assert_ne!(evil, *standard::Authorization);
+        assert_ne!(*standard::Authorization, evil);
+
+        let authorization = Header::forged("AUTHORIZATION", standard::Authorization.hash);
+        assert_eq!(authorization, *standard::Authorization);
+    }
+
+    #[test]
+    fn test_parse_preserving_case() {
+        for name in ["Content-Type", "content-type", "Content-type", "CONTENT-TYPE"] {
+            let header = unsafe {Header::parse_mainly_standard(name.as_bytes())}.unwrap();
+            assert_eq!(&*header, name);
+            assert_eq!(header, *standard::ContentType);
+        }
+        let header = unsafe {Header::parse_mainly_standard(b"x-Custom")}.unwrap();
+        assert_eq!(&*header, "x-Custom");
+    }
+
+    #[test]
+    fn test_standard_in_any_casing() {
+        for (standard, name) in Standard::ALL {
+            let name = std::str::from_utf8(name).unwrap();
+            for name in [name.to_owned(), name.to_ascii_lowercase(), name.to_ascii_uppercase()] {
+                let header = Standard::from_bytes(name.as_bytes()).map(|s| s.as_header());
+                assert_eq!(header.map(|h| h.hash), Some(standard.as_header().hash), "{name}");
+            }
+        }
+
+        for name in ["", "T", "TF", "Content-Typo", "Content_Type", "Content-Type ", "X-Custom", "Sec-Fetch-Modes", "ContentType"] {
+            assert!(Standard::from_bytes(name.as_bytes()).is_none(), "{name}");
+        }
+    }
+
+    #[test]
+    fn test_canonical() {
+        assert_eq!(Header::def("content-type").canonical(), "Content-Type");
+        assert_eq!(Header::def("etag").canonical(), "ETag");
+        assert_eq!(Header::def("www-AUTHENTICATE").canonical(), "WWW-Authenticate");
+        assert_eq!(Header::def("X-Custom-Header").canonical(), "X-Custom-Header");
+        assert_eq!(Header::def("x-custom-HEADER").canonical(), "X-Custom-Header");
+        assert_eq!(Header::def("-x--y").canonical(), "-X--Y");
+    }
 }
@@ -0,0 +1,342 @@
+//! Structured values of standard headers
+//! 
+//! *example.rs*
+//! ```
+//! use whttp::{Headers, header};
+//! use whttp::headers::typed::{ContentLength, LastModified};
+//! use whttp::util::IMFfixdate;
+//! 
+//! let mut headers = Headers::new();
+//! headers
+//!     .typed_set(ContentLength(42))
+//!     .typed_set(LastModified(IMFfixdate::of_unix_timestamp(784111777)));
+//! 
+//! assert_eq!(headers.get(header::ContentLength), Some("42"));
+//! assert_eq!(headers.get(header::LastModified), Some("Sun, 06 Nov 1994 08:49:37 GMT"));
+//! assert_eq!(headers.typed_get::<ContentLength>(), Some(ContentLength(42)));
+//! ```
+
+use super::{Header, Value, standard};
+use crate::{Method, util::IMFfixdate};
+
+/// Header whose value has a structure.
+/// 
+/// Implementing `From<Self> for Value` too enables to use it with
+/// `Headers::set`, `Response::with` and so on.
+pub trait TypedHeader: Sized {
+    /// typically one of `header::*`
+    const HEADER: &'static Header;
+
+    /// `None` when `value` is invalid as `Self::HEADER`
+    fn decode(value: &Value) -> Option<Self>;
+
+    fn encode(&self) -> Value;
+}
+
+macro_rules! typed_header_into_value {
+    ($($T:ident)*) => {$(
+        impl From<$T> for Value {
+            #[inline]
+            fn from(typed: $T) -> Self {
+                typed.encode()
+            }
+        }
+    )*};
+}
+
+/*=====================================================*/
+
+/// `Content-Length: <length>`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContentLength(pub usize);
+impl TypedHeader for ContentLength {
+    const HEADER: &'static Header = standard::ContentLength;
+
+    fn decode(value: &Value) -> Option<Self> {
+        let value = value.trim();
+        (value.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| value.parse().ok()).flatten()
+            .map(Self)
+    }
+
+    fn encode(&self) -> Value {
+        Value::from(self.0)
+    }
+}
+
+/// `Age: <delta-seconds>`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Age(pub u64);
+impl TypedHeader for Age {
+    const HEADER: &'static Header = standard::Age;
+
+    fn decode(value: &Value) -> Option<Self> {
+        let value = value.trim();
+        (value.bytes().all(|b| b.is_ascii_digit()))
+            .then(|| value.parse().ok()).flatten()
+            .map(Self)
+    }
+
+    fn encode(&self) -> Value {
+        Value::from(self.0.to_string())
+    }
+}
+
+/*=====================================================*/
+
+macro_rules! IMFfixdateHeader {
+    ($($name:ident)*) => {$(
+        #[doc = concat!("`", stringify!($name), ": <IMF-fixdate>`")]
+        #[derive(Clone, Copy, Debug, PartialEq)]
+        pub struct $name(pub IMFfixdate);
+        impl TypedHeader for $name {
+            const HEADER: &'static Header = standard::$name;
+
+            #[inline]
+            fn decode(value: &Value) -> Option<Self> {
+                IMFfixdate::parse(value).map(Self)
+            }
+
+            #[inline]
+            fn encode(&self) -> Value {
+                self.0.into()
+            }
+        }
+        typed_header_into_value! { $name }
+    )*};
+}
+IMFfixdateHeader! {
+    Date
+    Expires
+    LastModified
+    IfModifiedSince
+    IfUnmodifiedSince
+}
+
+/// `Retry-After: <delay-seconds>` or `Retry-After: <IMF-fixdate>`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RetryAfter {
+    Seconds(u64),
+    Date(IMFfixdate),
+}
+impl TypedHeader for RetryAfter {
+    const HEADER: &'static Header = standard::RetryAfter;
+
+    fn decode(value: &Value) -> Option<Self> {
+        match value.trim() {
+            secs if secs.bytes().all(|b| b.is_ascii_digit()) => secs.parse().ok().map(Self::Seconds),
+            date => IMFfixdate::parse(date).map(Self::Date)
+        }
+    }
+
+    fn encode(&self) -> Value {
+        match self {
+            Self::Seconds(secs) => Value::from(secs.to_string()),
+            Self::Date(date) => (*date).into()
+        }
+    }
+}
+
+/*=====================================================*/
+
+/// `Host: <host>[:<port>]`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Host {
+    pub host: String,
+    pub port: Option<u16>,
+}
+impl TypedHeader for Host {
+    const HEADER: &'static Header = standard::Host;
+
+    fn decode(value: &Value) -> Option<Self> {
+        let value = value.trim();
+        let (host, port) = match value.rsplit_once(':') {
+            Some((host, port)) if host.ends_with(']') || !host.contains(':') => {
+                (host, Some(port.parse().ok()?))
+            }
+            _ => (value, None)
+        };
+        (!host.is_empty() && !host.contains(|c: char| c.is_ascii_whitespace() || matches!(c, '/' | '?' | '#' | '@')))
+            .then(|| Self { host: host.into(), port })
+    }
+
+    fn encode(&self) -> Value {
+        Value::from(match self.port {
+            Some(port) => format!("{}:{port}", self.host),
+            None => self.host.clone()
+        })
+    }
+}
+
+/// `Location: <uri-reference>`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Location(pub String);
+impl TypedHeader for Location {
+    const HEADER: &'static Header = standard::Location;
+
+    fn decode(value: &Value) -> Option<Self> {
+        let value = value.trim();
+        (!value.is_empty() && !value.contains(' ')).then(|| Self(value.into()))
+    }
+
+    fn encode(&self) -> Value {
+        Value::from(self.0.clone())
+    }
+}
+
+/// `Allow: <method>, <method>, ...`
+#[derive(Clone, Debug, PartialEq)]
+pub struct Allow(pub Vec<Method>);
+impl TypedHeader for Allow {
+    const HEADER: &'static Header = standard::Allow;
+
+    /// unknown methods are ignored
+    fn decode(value: &Value) -> Option<Self> {
+        Some(Self(value.split(',')
+            .map(str::trim)
+            .filter(|m| !m.is_empty())
+            .filter_map(|m| Method::from_bytes(m.as_bytes()))
+            .collect()
+        ))
+    }
+
+    fn encode(&self) -> Value {
+        Value::from(self.0.iter().map(Method::as_str).collect::<Vec<_>>().join(", "))
+    }
+}
+
+/// `Vary: *` or `Vary: <header>, <header>, ...`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Vary {
+    Any,
+    Headers(Vec<String>),
+}
+impl TypedHeader for Vary {
+    const HEADER: &'static Header = standard::Vary;
+
+    fn decode(value: &Value) -> Option<Self> {
+        match value.trim() {
+            "*" => Some(Self::Any),
+            names => Some(Self::Headers(names.split(',')
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .map(String::from)
+                .collect()
+            ))
+        }
+    }
+
+    fn encode(&self) -> Value {
+        match self {
+            Self::Any => Value::new("*"),
+            Self::Headers(names) => Value::from(names.join(", "))
+        }
+    }
+}
+
+/*=====================================================*/
+
+/// `Content-Range: bytes <first>-<last>/<complete-length>`, where
+/// `range` is `None` for unsatisfied range ( `bytes */<complete-length>` ) and
+/// `complete_length` is `None` for unknown length ( `bytes <first>-<last>/*` ).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ContentRange {
+    pub range:           Option<(u64, u64)>,
+    pub complete_length: Option<u64>,
+}
+impl TypedHeader for ContentRange {
+    const HEADER: &'static Header = standard::ContentRange;
+
+    fn decode(value: &Value) -> Option<Self> {
+        fn number(s: &str) -> Option<u64> {
+            (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())).then(|| s.parse().ok()).flatten()
+        }
+
+        let (unit, rest) = value.trim().split_once(' ')?;
+        if !unit.eq_ignore_ascii_case("bytes") {return None}
+
+        let (range, complete_length) = rest.trim_start().split_once('/')?;
+        let complete_length = match complete_length {
+            "*" => None,
+            n => Some(number(n)?)
+        };
+        let range = match range {
+            "*" => None,
+            r => {
+                let (first, last) = r.split_once('-')?;
+                let (first, last) = (number(first)?, number(last)?);
+                if first > last || complete_length.is_some_and(|len| last >= len) {
+                    return None
+                }
+                Some((first, last))
+            }
+        };
+        if range.is_none() && complete_length.is_none() {
+            return None
+        }
+
+        Some(Self { range, complete_length })
+    }
+
+    fn encode(&self) -> Value {
+        let mut value = String::from("bytes ");
+        match self.range {
+            Some((first, last)) => value.push_str(&format!("{first}-{last}")),
+            None => value.push('*')
+        }
+        value.push('/');
+        match self.complete_length {
+            Some(len) => value.push_str(&len.to_string()),
+            None => value.push('*')
+        }
+        Value::from(value)
+    }
+}
+
+typed_header_into_value! {
+    ContentLength
+    Age
+    RetryAfter
+    Host
+    Location
+    Allow
+    Vary
+    ContentRange
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decode<T: TypedHeader>(value: &'static str) -> Option<T> {
+        T::decode(&Value::new(value))
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        assert_eq!(decode::<ContentLength>("42"), Some(ContentLength(42)));
+        assert_eq!(decode::<ContentLength>("+42"), None);
+        assert_eq!(decode::<Host>("example.com:8080"), Some(Host { host: "example.com".into(), port: Some(8080) }));
+        assert_eq!(decode::<Host>("[::1]:3000").map(|h| h.encode()), Some(Value::new("[::1]:3000")));
+        assert_eq!(decode::<Host>("[::1]"), Some(Host { host: "[::1]".into(), port: None }));
+        assert_eq!(decode::<Allow>("GET, HEAD,OPTIONS"), Some(Allow(vec![Method::GET, Method::HEAD, Method::OPTIONS])));
+        assert_eq!(Allow(vec![Method::GET, Method::POST]).encode(), "GET, POST");
+        assert_eq!(decode::<Vary>("*"), Some(Vary::Any));
+        assert_eq!(decode::<RetryAfter>("120"), Some(RetryAfter::Seconds(120)));
+        assert_eq!(decode::<RetryAfter>("Sun, 06 Nov 1994 08:49:37 GMT"), Some(RetryAfter::Date(IMFfixdate::of_unix_timestamp(784111777))));
+    }
+
+    #[test]
+    fn test_content_range() {
+        assert_eq!(decode::<ContentRange>("bytes 0-499/1234"), Some(ContentRange { range: Some((0, 499)), complete_length: Some(1234) }));
+        assert_eq!(decode::<ContentRange>("bytes */1234"), Some(ContentRange { range: None, complete_length: Some(1234) }));
+        assert_eq!(decode::<ContentRange>("bytes 0-499/*"), Some(ContentRange { range: Some((0, 499)), complete_length: None }));
+        assert_eq!(decode::<ContentRange>("bytes */*"), None);
+        assert_eq!(decode::<ContentRange>("bytes 500-499/1234"), None);
+        assert_eq!(decode::<ContentRange>("bytes 0-1234/1234"), None);
+        assert_eq!(ContentRange { range: Some((500, 999)), complete_length: Some(1234) }.encode(), "bytes 500-999/1234");
+    }
+}
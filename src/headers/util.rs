@@ -3,3 +3,52 @@ pub use datetime::IMFfixdate;
 
 mod cookies;
 pub use cookies::{cookie, setcookie};
+
+mod cache_control;
+pub use cache_control::CacheControl;
+
+mod cors;
+pub use cors::Cors;
+
+mod hsts;
+pub use hsts::Hsts;
+
+mod priority;
+pub use priority::Priority;
+
+pub mod csp;
+pub use csp::Csp;
+
+pub mod security;
+
+mod etag;
+pub use etag::{ETag, ETags};
+
+pub mod conditional;
+
+pub mod range;
+
+mod link;
+pub use link::Link;
+
+pub mod content_disposition;
+pub use content_disposition::ContentDisposition;
+
+mod base64;
+mod md5;
+mod sha256;
+mod random;
+
+pub mod auth;
+
+pub mod digest;
+
+pub mod forwarded;
+
+pub mod mime;
+pub use mime::Mime;
+
+pub mod negotiation;
+pub use negotiation::Negotiation;
+
+pub mod sfv;
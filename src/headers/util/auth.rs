@@ -0,0 +1,485 @@
+//! HTTP authentication ( RFC 9110 §11 )
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response, header};
+//! use whttp::util::auth::Credentials;
+//!
+//! fn handle(req: &Request) -> Response {
+//!     match req.authorization() {
+//!         Some(Credentials::Basic { user, password }) if user == "whttp" && password == "open sesame" => {
+//!             Response::OK().with_text(format!("Hello, {user}!"))
+//!         }
+//!         Some(Credentials::Bearer(token)) if token == "mF_9.B5f-4.1JqM" => {
+//!             Response::OK().with_text("Hello, bearer!")
+//!         }
+//!         _ => Response::Unauthorized()
+//!     }
+//! }
+//!
+//! let req = Request::GET("/")
+//!     .with(header::Authorization, Credentials::basic("whttp", "open sesame"));
+//! assert_eq!(req.header(header::Authorization), Some("Basic d2h0dHA6b3BlbiBzZXNhbWU="));
+//! assert_eq!(handle(&req).status(), whttp::Status::OK);
+//!
+//! let req = Request::GET("/")
+//!     .with(header::Authorization, "bearer mF_9.B5f-4.1JqM");
+//! assert_eq!(handle(&req).status(), whttp::Status::OK);
+//! ```
+
+use crate::headers::Value;
+use super::base64;
+use std::borrow::Cow;
+
+/// credentials of `Authorization` or `Proxy-Authorization`
+#[derive(Clone, PartialEq, Debug)]
+pub enum Credentials<'h> {
+    /// RFC 7617, decoded as UTF-8
+    Basic { user: Cow<'h, str>, password: Cow<'h, str> },
+    /// RFC 6750 §2.1
+    Bearer(&'h str),
+    /// other schemes, having either of token68 or auth-params
+    Other {
+        scheme:  &'h str,
+        token68: Option<&'h str>,
+        params:  Params<'h>,
+    },
+}
+
+/// auth-params in order, with unquoted values
+pub type Params<'h> = Vec<(&'h str, Cow<'h, str>)>;
+
+impl Credentials<'static> {
+    /// Panics if `user` contains `:`, which is not allowed in Basic.
+    pub fn basic(user: impl Into<Cow<'static, str>>, password: impl Into<Cow<'static, str>>) -> Self {
+        let user = user.into();
+        if user.contains(':') {panic!("user-id of Basic must not contain `:`")}
+        Self::Basic { user, password: password.into() }
+    }
+
+    /// Panics if `token` is not a valid token68.
+    pub fn bearer(token: &'static str) -> Self {
+        if !is_token68(token) {panic!("invalid Bearer token")}
+        Self::Bearer(token)
+    }
+}
+
+impl<'h> Credentials<'h> {
+    /// `None` for invalid credentials, including Basic with non-UTF-8 or
+    /// no `:` in the decoded user-pass
+    pub fn parse(credentials: &'h str) -> Option<Self> {
+        let (scheme, rest) = split_scheme(credentials.trim())?;
+
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let user_pass = String::from_utf8(base64::decode(rest).filter(|_| is_token68(rest))?).ok()?;
+            let (user, password) = user_pass.split_once(':')?;
+            Some(Self::Basic { user: Cow::Owned(user.into()), password: Cow::Owned(password.into()) })
+
+        } else if scheme.eq_ignore_ascii_case("Bearer") {
+            is_token68(rest).then_some(Self::Bearer(rest))
+
+        } else if rest.is_empty() || is_token68(rest) {
+            Some(Self::Other { scheme, token68: (!rest.is_empty()).then_some(rest), params: Vec::new() })
+
+        } else {
+            let (params, rest) = parse_params(rest)?;
+            rest.is_empty().then_some(Self::Other { scheme, token68: None, params })
+        }
+    }
+
+    #[inline]
+    pub fn scheme(&self) -> &str {
+        match self {
+            Self::Basic { .. } => "Basic",
+            Self::Bearer(_) => "Bearer",
+            Self::Other { scheme, .. } => scheme
+        }
+    }
+
+    /// auth-param of `name` ( case-insensitive ) for `Other`
+    pub fn param(&self, name: &str) -> Option<&str> {
+        match self {
+            Self::Other { params, .. } => params.iter()
+                .find_map(|(n, v)| n.eq_ignore_ascii_case(name).then_some(&**v)),
+            _ => None
+        }
+    }
+}
+
+impl std::fmt::Display for Credentials<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Basic { user, password } => write!(f, "Basic {}", base64::encode(format!("{user}:{password}"))),
+            Self::Bearer(token) => write!(f, "Bearer {token}"),
+            Self::Other { scheme, token68: Some(token68), .. } => write!(f, "{scheme} {token68}"),
+            Self::Other { scheme, token68: None, params } => {
+                f.write_str(scheme)?;
+                /* RFC 7616 §3.4 */
+                write_params(f, params, &["algorithm", "qop", "nc", "userhash"])
+            }
+        }
+    }
+}
+
+impl From<Credentials<'_>> for Value {
+    #[inline]
+    fn from(credentials: Credentials<'_>) -> Self {
+        Value::from(credentials.to_string())
+    }
+}
+
+/// challenge of `WWW-Authenticate` or `Proxy-Authenticate`
+///
+/// Multiple challenges are set by `append`ing them to the same header.
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Response, header};
+/// use whttp::util::auth::Challenge;
+///
+/// let res = Response::Unauthorized()
+///     .with(header::WWWAuthenticate, Challenge::bearer()
+///         .with_realm("example")
+///         .with_error("invalid_token")
+///         .with_error_description("The access token expired")
+///     );
+/// assert_eq!(
+///     res.header(header::WWWAuthenticate),
+///     Some(r#"Bearer realm="example", error="invalid_token", error_description="The access token expired""#)
+/// );
+///
+/// let mut res = Response::Unauthorized();
+/// res.append(header::WWWAuthenticate, Challenge::basic("simple"))
+///     .append(header::WWWAuthenticate, Challenge::new("Newauth").with_realm("apps").with_param("type", "1"));
+///
+/// let challenges = Challenge::parse_list(res.header(header::WWWAuthenticate).unwrap()).unwrap();
+/// assert_eq!(challenges.len(), 2);
+/// assert_eq!(challenges[0].scheme, "Basic");
+/// assert_eq!(challenges[0].param("realm"), Some("simple"));
+/// assert_eq!(challenges[1].param("type"), Some("1"));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Challenge<'h> {
+    pub scheme:  &'h str,
+    pub token68: Option<Cow<'h, str>>,
+    pub params:  Params<'h>,
+}
+
+impl Challenge<'static> {
+    /// Panics if `scheme` is not a token.
+    pub fn new(scheme: &'static str) -> Self {
+        if !is_token(scheme) {panic!("invalid auth-scheme")}
+        Self { scheme, token68: None, params: Vec::new() }
+    }
+
+    /// `Basic realm="<realm>", charset="UTF-8"` ( RFC 7617 )
+    pub fn basic(realm: impl Into<Cow<'static, str>>) -> Self {
+        Self::new("Basic").with_realm(realm).with_param("charset", "UTF-8")
+    }
+
+    /// `Bearer` ( RFC 6750 §3 ) to be added `realm`, `scope`, `error` and so on
+    pub fn bearer() -> Self {
+        Self::new("Bearer")
+    }
+
+    /// Set a param of `name`, replacing existing one.
+    pub fn with_param(mut self, name: &'static str, value: impl Into<Cow<'static, str>>) -> Self {
+        let value = value.into();
+        match self.params.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(name)) {
+            Some(param) => param.1 = value,
+            None => self.params.push((name, value))
+        }
+        self
+    }
+
+    /// Panics if `token68` is invalid.
+    pub fn with_token68(mut self, token68: impl Into<Cow<'static, str>>) -> Self {
+        let token68 = token68.into();
+        if !is_token68(&token68) {panic!("invalid token68")}
+        self.token68 = Some(token68);
+        self
+    }
+
+    #[inline]
+    pub fn with_realm(self, realm: impl Into<Cow<'static, str>>) -> Self {
+        self.with_param("realm", realm)
+    }
+
+    /// space-delimited scopes ( RFC 6750 §3 )
+    #[inline]
+    pub fn with_scope(self, scope: impl Into<Cow<'static, str>>) -> Self {
+        self.with_param("scope", scope)
+    }
+
+    /// like `invalid_request`, `invalid_token` or `insufficient_scope` ( RFC 6750 §3.1 )
+    #[inline]
+    pub fn with_error(self, error: impl Into<Cow<'static, str>>) -> Self {
+        self.with_param("error", error)
+    }
+
+    #[inline]
+    pub fn with_error_description(self, description: impl Into<Cow<'static, str>>) -> Self {
+        self.with_param("error_description", description)
+    }
+}
+
+impl<'h> Challenge<'h> {
+    /// Parse a list of challenges, `None` if any of them is invalid.
+    pub fn parse_list(challenges: &'h str) -> Option<Vec<Self>> {
+        let mut list = Vec::new();
+        let mut rest = challenges.trim();
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ',']);
+            if rest.is_empty() {break}
+
+            let (scheme, r) = split_scheme(rest)?;
+            let mut challenge = Challenge { scheme, token68: None, params: Vec::new() };
+
+            /* params end at the next challenge */
+            if let Some((params, r)) = parse_params(r) {
+                challenge.params = params;
+                rest = r;
+            } else {
+                let end = r.find(',').unwrap_or(r.len());
+                let token68 = r[..end].trim_end();
+                if !token68.is_empty() {
+                    if !is_token68(token68) {return None}
+                    challenge.token68 = Some(Cow::Borrowed(token68));
+                }
+                rest = &r[end..];
+            }
+
+            list.push(challenge);
+        }
+        (!list.is_empty()).then_some(list)
+    }
+
+    /// param of `name` ( case-insensitive )
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter().find_map(|(n, v)| n.eq_ignore_ascii_case(name).then_some(&**v))
+    }
+
+    #[inline]
+    pub fn realm(&self) -> Option<&str> {
+        self.param("realm")
+    }
+}
+
+impl std::fmt::Display for Challenge<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.scheme)?;
+        match &self.token68 {
+            Some(token68) => write!(f, " {token68}"),
+            /* RFC 7616 §3.3 */
+            None => write_params(f, &self.params, &["algorithm", "stale", "userhash"])
+        }
+    }
+}
+
+impl From<Challenge<'_>> for Value {
+    #[inline]
+    fn from(challenge: Challenge<'_>) -> Self {
+        Value::from(challenge.to_string())
+    }
+}
+
+/*=====================================================*/
+
+#[inline(always)]
+pub(super) const fn is_tchar(b: u8) -> bool {
+    matches!(b,
+        | b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+        | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z'
+    )
+}
+
+#[inline]
+pub(super) fn is_token(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(is_tchar)
+}
+
+/// `token68 = 1*( ALPHA / DIGIT / "-" / "." / "_" / "~" / "+" / "/" ) *"="`
+pub(super) fn is_token68(s: &str) -> bool {
+    let body = s.trim_end_matches('=');
+    !body.is_empty() && body.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'+' | b'/'))
+}
+
+/// `auth-scheme [ 1*SP rest ]`, where `rest` may start with `,` of the next list element
+pub(super) fn split_scheme(s: &str) -> Option<(&str, &str)> {
+    let end = s.bytes().position(|b| !is_tchar(b)).unwrap_or(s.len());
+    if end == 0 {return None}
+    let (scheme, rest) = s.split_at(end);
+    match rest.trim_start_matches(' ') {
+        trimmed if trimmed.len() == rest.len() && !(rest.is_empty() || rest.starts_with(',')) => None,
+        trimmed => Some((scheme, trimmed))
+    }
+}
+
+/// Parse `auth-param *( OWS "," OWS auth-param )` at the start of `s`
+/// and return them with the rest starting at the first non-param element.
+pub(super) fn parse_params(s: &str) -> Option<(Params<'_>, &str)> {
+    let mut params = Vec::new();
+    let mut rest = s;
+    loop {
+        rest = rest.trim_start_matches([' ', '\t', ',']);
+        let Some((name, value, r)) = parse_param(rest) else {break};
+        params.push((name, value));
+        rest = r;
+    }
+    (!params.is_empty()).then_some((params, rest.trim_start()))
+}
+
+/// `token BWS "=" BWS ( token / quoted-string )`
+fn parse_param(s: &str) -> Option<(&str, Cow<'_, str>, &str)> {
+    let name_end = s.bytes().position(|b| !is_tchar(b)).unwrap_or(s.len());
+    if name_end == 0 {return None}
+    let (name, rest) = s.split_at(name_end);
+    let rest = rest.trim_start_matches([' ', '\t']).strip_prefix('=')?.trim_start_matches([' ', '\t']);
+
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let mut value = String::new();
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => {value.push(c); escaped = false}
+                '\\' => escaped = true,
+                '"' => return Some((name, Cow::Owned(value), &quoted[i + 1..])),
+                _ => value.push(c)
+            }
+        }
+        None
+    } else {
+        let value_end = rest.bytes().position(|b| !is_tchar(b)).unwrap_or(rest.len());
+        if value_end == 0 {return None}
+        let (value, rest) = rest.split_at(value_end);
+        match rest.trim_start().as_bytes().first() {
+            None | Some(b',') => Some((name, Cow::Borrowed(value), rest)),
+            _ => None
+        }
+    }
+}
+
+/// Write `params` as quoted-strings, except for the names in `tokens`
+/// whose values are written bare if they are tokens.
+pub(super) fn write_params(
+    f:      &mut std::fmt::Formatter<'_>,
+    params: &[(&str, Cow<'_, str>)],
+    tokens: &[&str],
+) -> std::fmt::Result {
+    for (i, (name, value)) in params.iter().enumerate() {
+        f.write_str(if i == 0 {" "} else {", "})?;
+        if is_token(value) && tokens.iter().any(|t| t.eq_ignore_ascii_case(name)) {
+            write!(f, "{name}={value}")?
+        } else {
+            write!(f, "{name}=\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))?
+        }
+    }
+    Ok(())
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_basic() {
+        /* RFC 7617 §2 */
+        assert_eq!(
+            Credentials::parse("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+            Some(Credentials::basic("Aladdin", "open sesame"))
+        );
+        assert_eq!(
+            Credentials::parse("BASIC  QWxhZGRpbjpvcGVuIHNlc2FtZQ=="),
+            Some(Credentials::basic("Aladdin", "open sesame"))
+        );
+        /* password may contain `:` */
+        assert_eq!(
+            Credentials::parse(&format!("Basic {}", base64::encode("user:pa:ss"))),
+            Some(Credentials::basic("user", "pa:ss"))
+        );
+        /* RFC 7617 §2.1, UTF-8 */
+        assert_eq!(
+            Credentials::parse("Basic dGVzdDoxMjPCow=="),
+            Some(Credentials::basic("test", "123£"))
+        );
+        for invalid in ["Basic", "Basic QWxhZGRpbg==", "Basic !!!", "Basic/QWxh", "Basic gA==", "BasicQWxhZGRpbjpvcGVuIHNlc2FtZQ=="] {
+            assert!(!matches!(Credentials::parse(invalid), Some(Credentials::Basic { .. })), "{invalid}");
+        }
+        assert_eq!(Credentials::basic("Aladdin", "open sesame").to_string(), "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==");
+    }
+
+    #[test]
+    fn test_bearer() {
+        assert_eq!(Credentials::parse("Bearer mF_9.B5f-4.1JqM"), Some(Credentials::Bearer("mF_9.B5f-4.1JqM")));
+        assert_eq!(Credentials::parse("bearer abc/def+ghi=="), Some(Credentials::Bearer("abc/def+ghi==")));
+        assert_eq!(Credentials::parse("Bearer"), None);
+        assert_eq!(Credentials::parse("Bearer a b"), None);
+        assert_eq!(Credentials::parse("Bearer a=b=c"), None);
+    }
+
+    #[test]
+    fn test_other() {
+        assert_eq!(
+            Credentials::parse("Negotiate YIIBzwYGKwYBBQUCoIIBwzCCAb+gMDAuBgkqhkiC9xIBAgIGCSqGSIb3EgECAgYKKwYBBAGCNwICHgYKKwYBBAGCNwICCg=="),
+            Some(Credentials::Other {
+                scheme:  "Negotiate",
+                token68: Some("YIIBzwYGKwYBBQUCoIIBwzCCAb+gMDAuBgkqhkiC9xIBAgIGCSqGSIb3EgECAgYKKwYBBAGCNwICHgYKKwYBBAGCNwICCg=="),
+                params:  vec![],
+            })
+        );
+
+        let digest = Credentials::parse(r#"Digest username="Mufasa", realm="http-auth@example.org" , qop=auth,nc=00000001, response = "a\"b""#).unwrap();
+        assert_eq!(digest.scheme(), "Digest");
+        assert_eq!(digest.param("USERNAME"), Some("Mufasa"));
+        assert_eq!(digest.param("realm"), Some("http-auth@example.org"));
+        assert_eq!(digest.param("qop"), Some("auth"));
+        assert_eq!(digest.param("nc"), Some("00000001"));
+        assert_eq!(digest.param("response"), Some("a\"b"));
+        assert_eq!(digest.to_string(), r#"Digest username="Mufasa", realm="http-auth@example.org", qop=auth, nc=00000001, response="a\"b""#);
+
+        assert_eq!(Credentials::parse(r#"Digest username="Mufasa"#), None);
+        assert_eq!(Credentials::parse(r#"Digest username=Mufasa realm=x"#), None);
+    }
+
+    #[test]
+    fn test_challenge() {
+        assert_eq!(
+            Challenge::basic("WallyWorld").to_string(),
+            r#"Basic realm="WallyWorld", charset="UTF-8""#
+        );
+        assert_eq!(
+            Challenge::bearer().with_realm("example").with_scope("openid profile").with_error("insufficient_scope").to_string(),
+            r#"Bearer realm="example", scope="openid profile", error="insufficient_scope""#
+        );
+        assert_eq!(
+            Challenge::new("Digest").with_realm("a\"b").with_param("algorithm", "SHA-256").with_param("stale", "true").to_string(),
+            r#"Digest realm="a\"b", algorithm=SHA-256, stale=true"#
+        );
+        assert_eq!(Challenge::new("Negotiate").to_string(), "Negotiate");
+        assert_eq!(Challenge::new("Negotiate").with_token68("abc=").to_string(), "Negotiate abc=");
+    }
+
+    #[test]
+    fn test_parse_challenges() {
+        /* RFC 9110 §11.6.1 */
+        let challenges = Challenge::parse_list(r#"Basic realm="simple", Newauth realm="apps", type=1, title="Login to \"apps\"""#).unwrap();
+        assert_eq!(challenges, [
+            Challenge { scheme: "Basic", token68: None, params: vec![("realm", "simple".into())] },
+            Challenge { scheme: "Newauth", token68: None, params: vec![("realm", "apps".into()), ("type", "1".into()), ("title", "Login to \"apps\"".into())] },
+        ]);
+
+        let challenges = Challenge::parse_list("Negotiate, NTLM abc==,Bearer realm=x,,").unwrap();
+        assert_eq!(challenges, [
+            Challenge { scheme: "Negotiate", token68: None, params: vec![] },
+            Challenge { scheme: "NTLM", token68: Some("abc==".into()), params: vec![] },
+            Challenge { scheme: "Bearer", token68: None, params: vec![("realm", "x".into())] },
+        ]);
+
+        for invalid in ["", ",", "Basic realm=\"x", "Basic a b", "Basic realm=x y", "=x"] {
+            assert_eq!(Challenge::parse_list(invalid), None, "{invalid}");
+        }
+    }
+}
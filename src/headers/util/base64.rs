@@ -0,0 +1,87 @@
+//! standard base64 ( RFC 4648 §4 ) for header values
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub(crate) fn encode(bytes: impl AsRef<[u8]>) -> String {
+    let bytes = bytes.as_ref();
+
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = match *chunk {
+            [a]       => (a as u32) << 16,
+            [a, b]    => (a as u32) << 16 | (b as u32) << 8,
+            [a, b, c] => (a as u32) << 16 | (b as u32) << 8 | c as u32,
+            _ => unreachable!()
+        };
+        for i in 0..4 {
+            encoded.push(if i <= chunk.len() {
+                ALPHABET[(n >> (18 - 6 * i) & 0b111111) as usize] as char
+            } else {
+                '='
+            });
+        }
+    }
+    encoded
+}
+
+/// `None` for invalid base64, accepting also unpadded input
+pub(crate) fn decode(encoded: &str) -> Option<Vec<u8>> {
+    const fn sextet(b: u8) -> Option<u32> {
+        match b {
+            b'A'..=b'Z' => Some((b - b'A') as u32),
+            b'a'..=b'z' => Some((b - b'a' + 26) as u32),
+            b'0'..=b'9' => Some((b - b'0' + 52) as u32),
+            b'+' => Some(62),
+            b'/' => Some(63),
+            _ => None
+        }
+    }
+
+    let encoded = encoded.as_bytes();
+    let unpadded = match encoded {
+        [rest @ .., b'=', b'='] | [rest @ .., b'='] if encoded.len().is_multiple_of(4) => rest,
+        _ => encoded
+    };
+    if unpadded.len() % 4 == 1 {return None}
+
+    let mut decoded = Vec::with_capacity(unpadded.len() * 3 / 4);
+    for chunk in unpadded.chunks(4) {
+        let mut n = 0;
+        for (i, b) in chunk.iter().enumerate() {
+            n |= sextet(*b)? << (18 - 6 * i);
+        }
+        decoded.push((n >> 16) as u8);
+        if chunk.len() > 2 {decoded.push((n >> 8) as u8)}
+        if chunk.len() > 3 {decoded.push(n as u8)}
+    }
+    Some(decoded)
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64() {
+        /* RFC 4648 §10 */
+        for (plain, encoded) in [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ] {
+            assert_eq!(encode(plain), encoded);
+            assert_eq!(decode(encoded).as_deref(), Some(plain.as_bytes()));
+            assert_eq!(decode(encoded.trim_end_matches('=')).as_deref(), Some(plain.as_bytes()));
+        }
+        for invalid in ["Z", "Zg=", "Zm9v!", "Zm 9v", "=Zm9"] {
+            assert_eq!(decode(invalid), None, "{invalid}");
+        }
+    }
+}
@@ -0,0 +1,328 @@
+use crate::headers::{Header, Value, TypedHeader, standard};
+
+/// `Cache-Control` directives ( RFC 9111 §5.2 ) for both requests and responses
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Request, Response, header};
+/// use whttp::util::CacheControl;
+///
+/// fn handle(req: &Request) -> Response {
+///     let cc = req.header(header::CacheControl)
+///         .map(CacheControl::parse)
+///         .unwrap_or_default();
+///     if cc.only_if_cached {
+///         return Response::GatewayTimeout()
+///     }
+///
+///     Response::OK()
+///         .with(header::CacheControl, CacheControl::new()
+///             .public()
+///             .max_age(3600)
+///             .stale_while_revalidate(60)
+///         )
+///         .with_text("Hello, world!")
+/// }
+///
+/// let res = handle(&Request::GET("/"));
+/// assert_eq!(res.header(header::CacheControl), Some("public, max-age=3600, stale-while-revalidate=60"));
+/// ```
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct CacheControl {
+    /* response directives */
+    pub public:                 bool,
+    pub private:                bool,
+    pub no_cache:               bool,
+    pub no_store:               bool,
+    pub no_transform:           bool,
+    pub must_revalidate:        bool,
+    pub proxy_revalidate:       bool,
+    pub must_understand:        bool,
+    pub immutable:              bool,
+    pub max_age:                Option<u64>,
+    pub s_maxage:               Option<u64>,
+    pub stale_while_revalidate: Option<u64>,
+    pub stale_if_error:         Option<u64>,
+
+    /* request-only directives */
+    /// `Some(None)` for `max-stale` without a value ( any staleness is acceptable )
+    pub max_stale:              Option<Option<u64>>,
+    pub min_fresh:              Option<u64>,
+    pub only_if_cached:         bool,
+
+    /// unknown directives and their arguments
+    pub extensions:             Vec<(String, Option<String>)>,
+}
+
+macro_rules! flags {
+    ($( $field:ident = $directive:literal ),*) => {
+        impl CacheControl {$(
+            #[doc = concat!("`", $directive, "`")]
+            #[inline]
+            pub fn $field(mut self) -> Self {
+                self.$field = true;
+                self
+            }
+        )*}
+    };
+}
+flags! {
+    public           = "public",
+    private          = "private",
+    no_cache         = "no-cache",
+    no_store         = "no-store",
+    no_transform     = "no-transform",
+    must_revalidate  = "must-revalidate",
+    proxy_revalidate = "proxy-revalidate",
+    must_understand  = "must-understand",
+    immutable        = "immutable",
+    only_if_cached   = "only-if-cached"
+}
+
+macro_rules! seconds {
+    ($( $field:ident = $directive:literal ),*) => {
+        impl CacheControl {$(
+            #[doc = concat!("`", $directive, "=<seconds>`")]
+            #[inline]
+            pub fn $field(mut self, seconds: u64) -> Self {
+                self.$field = Some(seconds);
+                self
+            }
+        )*}
+    };
+}
+seconds! {
+    max_age                = "max-age",
+    s_maxage               = "s-maxage",
+    stale_while_revalidate = "stale-while-revalidate",
+    stale_if_error         = "stale-if-error",
+    min_fresh              = "min-fresh"
+}
+
+impl CacheControl {
+    #[inline]
+    pub const fn new() -> Self {
+        Self {
+            public:                 false,
+            private:                false,
+            no_cache:               false,
+            no_store:               false,
+            no_transform:           false,
+            must_revalidate:        false,
+            proxy_revalidate:       false,
+            must_understand:        false,
+            immutable:              false,
+            max_age:                None,
+            s_maxage:               None,
+            stale_while_revalidate: None,
+            stale_if_error:         None,
+            max_stale:              None,
+            min_fresh:              None,
+            only_if_cached:         false,
+            extensions:             Vec::new(),
+        }
+    }
+
+    /// `max-stale[=<seconds>]`, where `None` accepts any staleness
+    #[inline]
+    pub fn max_stale(mut self, seconds: Option<u64>) -> Self {
+        self.max_stale = Some(seconds);
+        self
+    }
+
+    #[inline]
+    pub fn extension(mut self, name: impl Into<String>, argument: Option<String>) -> Self {
+        self.extensions.push((name.into(), argument));
+        self
+    }
+
+    /// Parse directives leniently:
+    ///
+    /// - directive names are case-insensitive
+    /// - quoted-string arguments are unescaped, and may contain `,`
+    /// - field-name arguments of `no-cache` and `private` are ignored
+    /// - directives with an invalid delta-seconds are ignored
+    /// - unknown directives go to `extensions`
+    pub fn parse(cache_control: &str) -> Self {
+        fn seconds(argument: Option<&str>) -> Option<u64> {
+            let argument = argument?;
+            (!argument.is_empty() && argument.bytes().all(|b| b.is_ascii_digit()))
+                // too large delta-seconds is taken as 2^31 ( RFC 9111 §1.2.2 )
+                .then(|| argument.parse().unwrap_or(1 << 31))
+        }
+
+        fn unquote(argument: &str) -> std::borrow::Cow<'_, str> {
+            match argument.strip_prefix('"').and_then(|a| a.strip_suffix('"')) {
+                None => argument.into(),
+                Some(quoted) if !quoted.contains('\\') => quoted.into(),
+                Some(quoted) => {
+                    let mut unescaped = String::with_capacity(quoted.len());
+                    let mut chars = quoted.chars();
+                    while let Some(c) = chars.next() {
+                        unescaped.push(match c {
+                            '\\' => chars.next().unwrap_or(c),
+                            _ => c
+                        });
+                    }
+                    unescaped.into()
+                }
+            }
+        }
+
+        let mut this = Self::new();
+        for directive in super::negotiation::split_list(cache_control) {
+            let (name, argument) = match directive.split_once('=') {
+                Some((name, argument)) => (name.trim_end(), Some(unquote(argument.trim()))),
+                None => (directive, None)
+            };
+            let argument = argument.as_deref();
+
+            match &*name.to_ascii_lowercase() {
+                "public"                 => this.public = true,
+                "private"                => this.private = true,
+                "no-cache"               => this.no_cache = true,
+                "no-store"               => this.no_store = true,
+                "no-transform"           => this.no_transform = true,
+                "must-revalidate"        => this.must_revalidate = true,
+                "proxy-revalidate"       => this.proxy_revalidate = true,
+                "must-understand"        => this.must_understand = true,
+                "immutable"              => this.immutable = true,
+                "only-if-cached"         => this.only_if_cached = true,
+                "max-age"                => this.max_age = seconds(argument).or(this.max_age),
+                "s-maxage"               => this.s_maxage = seconds(argument).or(this.s_maxage),
+                "stale-while-revalidate" => this.stale_while_revalidate = seconds(argument).or(this.stale_while_revalidate),
+                "stale-if-error"         => this.stale_if_error = seconds(argument).or(this.stale_if_error),
+                "min-fresh"              => this.min_fresh = seconds(argument).or(this.min_fresh),
+                "max-stale" => match argument {
+                    None => this.max_stale = Some(None),
+                    some => if let Some(s) = seconds(some) {this.max_stale = Some(Some(s))}
+                }
+                _ => this.extensions.push((name.into(), argument.map(String::from)))
+            }
+        }
+        this
+    }
+}
+
+impl std::fmt::Display for CacheControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut directives = Vec::<std::borrow::Cow<'_, str>>::new();
+
+        for (flag, directive) in [
+            (self.public,           "public"),
+            (self.private,          "private"),
+            (self.no_cache,         "no-cache"),
+            (self.no_store,         "no-store"),
+            (self.no_transform,     "no-transform"),
+            (self.must_revalidate,  "must-revalidate"),
+            (self.proxy_revalidate, "proxy-revalidate"),
+            (self.must_understand,  "must-understand"),
+            (self.immutable,        "immutable"),
+        ] {
+            if flag {directives.push(directive.into())}
+        }
+        for (seconds, directive) in [
+            (self.max_age,                "max-age"),
+            (self.s_maxage,               "s-maxage"),
+            (self.stale_while_revalidate, "stale-while-revalidate"),
+            (self.stale_if_error,         "stale-if-error"),
+        ] {
+            if let Some(s) = seconds {directives.push(format!("{directive}={s}").into())}
+        }
+        match self.max_stale {
+            Some(Some(s)) => directives.push(format!("max-stale={s}").into()),
+            Some(None) => directives.push("max-stale".into()),
+            None => ()
+        }
+        if let Some(s) = self.min_fresh {
+            directives.push(format!("min-fresh={s}").into())
+        }
+        if self.only_if_cached {
+            directives.push("only-if-cached".into())
+        }
+        for (name, argument) in &self.extensions {
+            directives.push(match argument {
+                Some(a) if a.bytes().all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)) => format!("{name}={a}").into(),
+                Some(a) => format!("{name}=\"{}\"", a.replace('\\', "\\\\").replace('"', "\\\"")).into(),
+                None => name.into()
+            })
+        }
+
+        f.write_str(&directives.join(", "))
+    }
+}
+
+const _: () = {
+    impl From<CacheControl> for Value {
+        #[inline]
+        fn from(cache_control: CacheControl) -> Self {
+            Value::from(cache_control.to_string())
+        }
+    }
+
+    impl TypedHeader for CacheControl {
+        const HEADER: &'static Header = standard::CacheControl;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            Some(CacheControl::parse(value))
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            CacheControl::parse("Max-Age=60, no-cache=\"Set-Cookie\", private, s-maxage=\"120\", max-age=abc"),
+            CacheControl::new().max_age(60).no_cache().private().s_maxage(120)
+        );
+        assert_eq!(
+            CacheControl::parse("max-stale, only-if-cached, min-fresh=10"),
+            CacheControl::new().max_stale(None).only_if_cached().min_fresh(10)
+        );
+        assert_eq!(
+            CacheControl::parse("max-age=99999999999999999999, community=\"UCI\""),
+            CacheControl::new().max_age(1 << 31).extension("community", Some("UCI".into()))
+        );
+
+        /* `,` in quoted-string arguments */
+        assert_eq!(
+            CacheControl::parse("no-cache=\"a, b\", private=\"Set-Cookie, Authorization\", max-age=5"),
+            CacheControl::new().no_cache().private().max_age(5)
+        );
+        assert_eq!(
+            CacheControl::parse("ext=\"x, \\\"y\\\\\", no-store"),
+            CacheControl::new().no_store().extension("ext", Some("x, \"y\\".into()))
+        );
+        let cc = CacheControl::new().extension("ext", Some("a, \"b\"".into()));
+        assert_eq!(CacheControl::parse(&cc.to_string()), cc);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            CacheControl::new().no_cache().must_revalidate().to_string(),
+            "no-cache, must-revalidate"
+        );
+        assert_eq!(
+            CacheControl::new().immutable().max_age(31536000).public().to_string(),
+            "public, immutable, max-age=31536000"
+        );
+        assert_eq!(
+            CacheControl::new().max_stale(Some(5)).extension("ext", Some("a b".into())).to_string(),
+            "max-stale=5, ext=\"a b\""
+        );
+    }
+}
@@ -0,0 +1,267 @@
+//! Conditional requests ( RFC 9110 §13 )
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response, header};
+//! use whttp::util::{ETag, IMFfixdate};
+//! use whttp::util::conditional::{self, Precondition, Validators};
+//!
+//! fn get_document(req: &Request, document: &'static str) -> Response {
+//!     let validators = Validators::new()
+//!         .etag(ETag::of_payload(document.as_bytes()))
+//!         .last_modified(IMFfixdate::of_unix_timestamp(784111777));
+//!
+//!     let mut res = match conditional::evaluate(req, Some(&validators)) {
+//!         Precondition::Proceed            => Response::OK().with_text(document),
+//!         Precondition::NotModified        => Response::NotModified(),
+//!         Precondition::PreconditionFailed => return Response::PreconditionFailed(),
+//!     };
+//!     validators.apply(&mut res);
+//!     res
+//! }
+//!
+//! let res = get_document(&Request::GET("/doc"), "Hello");
+//! let etag = res.header(header::ETag).unwrap().to_owned();
+//! assert_eq!(res.status(), whttp::Status::OK);
+//!
+//! let res = get_document(&Request::GET("/doc").with(header::IfNoneMatch, etag), "Hello");
+//! assert_eq!(res.status(), whttp::Status::NotModified);
+//! assert_eq!(res.body(), None);
+//! ```
+
+use super::{ETag, ETags, IMFfixdate};
+use crate::{Request, Response, Method};
+use crate::headers::standard::{
+    ETag as ETagHeader, LastModified,
+    IfMatch, IfNoneMatch, IfModifiedSince, IfUnmodifiedSince, IfRange, Range,
+};
+
+/// result of evaluating preconditions
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Precondition {
+    /// perform the method normally
+    Proceed,
+    /// respond `304 Not Modified` ( only for `GET` and `HEAD` )
+    NotModified,
+    /// respond `412 Precondition Failed`
+    PreconditionFailed,
+}
+
+/// validators of the selected representation
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Validators {
+    pub etag:          Option<ETag>,
+    pub last_modified: Option<IMFfixdate>,
+}
+
+impl Validators {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { etag: None, last_modified: None }
+    }
+
+    #[inline]
+    pub fn etag(mut self, etag: ETag) -> Self {
+        self.etag = Some(etag);
+        self
+    }
+
+    #[inline]
+    pub fn last_modified(mut self, last_modified: IMFfixdate) -> Self {
+        self.last_modified = Some(last_modified);
+        self
+    }
+
+    /// set `ETag` and `Last-Modified` to `res`
+    pub fn apply(&self, res: &mut Response) {
+        if let Some(etag) = &self.etag {
+            res.set(ETagHeader, etag.clone());
+        }
+        if let Some(last_modified) = self.last_modified {
+            res.set(LastModified, last_modified);
+        }
+    }
+}
+
+/// Evaluate preconditions of `req` in the order of RFC 9110 §13.2.2,
+/// where `current` is `None` when the target resource has no current
+/// representation.
+///
+/// Evaluation of `If-Range` is separated into [`if_range`].
+pub fn evaluate(req: &Request, current: Option<&Validators>) -> Precondition {
+    let etag = current.and_then(|c| c.etag.as_ref());
+    let last_modified = current.and_then(|c| c.last_modified);
+
+    /* 1. If-Match, or 2. If-Unmodified-Since when If-Match is absent */
+    if let Some(if_match) = req.header(IfMatch) {
+        let matched = match ETags::parse(if_match) {
+            ETags::Any => current.is_some(),
+            ETags::List(list) => etag.is_some_and(|etag| list.iter().any(|e| e.strong_eq(etag)))
+        };
+        if !matched {
+            return Precondition::PreconditionFailed
+        }
+    } else if let (Some(since), Some(last_modified)) = (
+        req.header(IfUnmodifiedSince).and_then(IMFfixdate::parse),
+        last_modified
+    ) {
+        if last_modified > since {
+            return Precondition::PreconditionFailed
+        }
+    }
+
+    let is_get_or_head = matches!(req.method(), Method::GET | Method::HEAD);
+
+    /* 3. If-None-Match, or 4. If-Modified-Since when If-None-Match is absent */
+    if let Some(if_none_match) = req.header(IfNoneMatch) {
+        let matched = match ETags::parse(if_none_match) {
+            ETags::Any => current.is_some(),
+            ETags::List(list) => etag.is_some_and(|etag| list.iter().any(|e| e.weak_eq(etag)))
+        };
+        if matched {
+            return if is_get_or_head {
+                Precondition::NotModified
+            } else {
+                Precondition::PreconditionFailed
+            }
+        }
+    } else if is_get_or_head {
+        if let (Some(since), Some(last_modified)) = (
+            req.header(IfModifiedSince).and_then(IMFfixdate::parse),
+            last_modified
+        ) {
+            if last_modified <= since {
+                return Precondition::NotModified
+            }
+        }
+    }
+
+    Precondition::Proceed
+}
+
+/// Whether `Range` of `req` should be processed ( RFC 9110 §13.1.5 ):
+///
+/// - `false` without `Range` or for methods other than `GET`
+/// - `true` without `If-Range`
+/// - otherwise, `If-Range` must match `current` by strong comparison
+///   of entity tags or exact match of the date
+pub fn if_range(req: &Request, current: &Validators) -> bool {
+    if req.method() != Method::GET || req.header(Range).is_none() {
+        return false
+    }
+    let Some(if_range) = req.header(IfRange) else {
+        return true
+    };
+
+    match IMFfixdate::parse(if_range.trim()) {
+        Some(date) => current.last_modified == Some(date),
+        None => match (ETag::parse(if_range), &current.etag) {
+            (Some(e), Some(etag)) => e.strong_eq(etag),
+            _ => false
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header;
+
+    const EARLIER: u64 = 784111777;
+    const LATER:   u64 = 784111777 + 60;
+
+    fn date(secs: u64) -> String {
+        IMFfixdate::of_unix_timestamp(secs).to_string()
+    }
+
+    fn validators() -> Validators {
+        Validators::new()
+            .etag(ETag::strong("v1"))
+            .last_modified(IMFfixdate::of_unix_timestamp(EARLIER))
+    }
+
+    #[test]
+    fn test_if_match() {
+        let v = validators();
+        let put = || Request::PUT("/doc");
+
+        assert_eq!(evaluate(&put().with(header::IfMatch, "\"v1\""), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&put().with(header::IfMatch, "\"v0\", \"v1\""), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&put().with(header::IfMatch, "W/\"v1\""), Some(&v)), Precondition::PreconditionFailed);
+        assert_eq!(evaluate(&put().with(header::IfMatch, "\"v0\""), Some(&v)), Precondition::PreconditionFailed);
+        assert_eq!(evaluate(&put().with(header::IfMatch, "*"), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&put().with(header::IfMatch, "*"), None), Precondition::PreconditionFailed);
+
+        /* If-Unmodified-Since is ignored when If-Match is present */
+        assert_eq!(evaluate(&put()
+            .with(header::IfMatch, "\"v1\"")
+            .with(header::IfUnmodifiedSince, date(EARLIER - 1)), Some(&v)
+        ), Precondition::Proceed);
+    }
+
+    #[test]
+    fn test_if_unmodified_since() {
+        let v = validators();
+        let put = || Request::PUT("/doc");
+
+        assert_eq!(evaluate(&put().with(header::IfUnmodifiedSince, date(EARLIER)), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&put().with(header::IfUnmodifiedSince, date(EARLIER - 1)), Some(&v)), Precondition::PreconditionFailed);
+        /* invalid date or no Last-Modified: ignored */
+        assert_eq!(evaluate(&put().with(header::IfUnmodifiedSince, "yesterday"), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&put().with(header::IfUnmodifiedSince, date(EARLIER - 1)), Some(&Validators::new())), Precondition::Proceed);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let v = validators();
+
+        assert_eq!(evaluate(&Request::GET("/doc").with(header::IfNoneMatch, "W/\"v1\""), Some(&v)), Precondition::NotModified);
+        assert_eq!(evaluate(&Request::HEAD("/doc").with(header::IfNoneMatch, "\"v1\""), Some(&v)), Precondition::NotModified);
+        assert_eq!(evaluate(&Request::GET("/doc").with(header::IfNoneMatch, "\"v0\""), Some(&v)), Precondition::Proceed);
+        assert_eq!(evaluate(&Request::PUT("/doc").with(header::IfNoneMatch, "\"v1\""), Some(&v)), Precondition::PreconditionFailed);
+        /* `If-None-Match: *` for creating only if missing */
+        assert_eq!(evaluate(&Request::PUT("/doc").with(header::IfNoneMatch, "*"), None), Precondition::Proceed);
+        assert_eq!(evaluate(&Request::PUT("/doc").with(header::IfNoneMatch, "*"), Some(&v)), Precondition::PreconditionFailed);
+
+        /* If-Modified-Since is ignored when If-None-Match is present */
+        assert_eq!(evaluate(&Request::GET("/doc")
+            .with(header::IfNoneMatch, "\"v0\"")
+            .with(header::IfModifiedSince, date(LATER)), Some(&v)
+        ), Precondition::Proceed);
+
+        /* If-Match is evaluated first */
+        assert_eq!(evaluate(&Request::GET("/doc")
+            .with(header::IfMatch, "\"v0\"")
+            .with(header::IfNoneMatch, "\"v1\""), Some(&v)
+        ), Precondition::PreconditionFailed);
+    }
+
+    #[test]
+    fn test_if_modified_since() {
+        let v = validators();
+
+        assert_eq!(evaluate(&Request::GET("/doc").with(header::IfModifiedSince, date(EARLIER)), Some(&v)), Precondition::NotModified);
+        assert_eq!(evaluate(&Request::GET("/doc").with(header::IfModifiedSince, date(LATER)), Some(&v)), Precondition::NotModified);
+        assert_eq!(evaluate(&Request::GET("/doc").with(header::IfModifiedSince, date(EARLIER - 1)), Some(&v)), Precondition::Proceed);
+        /* only for GET and HEAD */
+        assert_eq!(evaluate(&Request::POST("/doc").with(header::IfModifiedSince, date(LATER)), Some(&v)), Precondition::Proceed);
+    }
+
+    #[test]
+    fn test_if_range() {
+        let v = validators();
+        let get = || Request::GET("/doc").with(header::Range, "bytes=0-9");
+
+        assert!(if_range(&get(), &v));
+        assert!(if_range(&get().with(header::IfRange, "\"v1\""), &v));
+        assert!(if_range(&get().with(header::IfRange, date(EARLIER)), &v));
+        assert!(!if_range(&get().with(header::IfRange, "W/\"v1\""), &v));
+        assert!(!if_range(&get().with(header::IfRange, "\"v0\""), &v));
+        assert!(!if_range(&get().with(header::IfRange, date(LATER)), &v));
+        assert!(!if_range(&Request::GET("/doc"), &v));
+        assert!(!if_range(&Request::POST("/doc").with(header::Range, "bytes=0-9"), &v));
+    }
+}
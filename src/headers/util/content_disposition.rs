@@ -0,0 +1,366 @@
+use crate::headers::{Header, Value, TypedHeader, standard};
+use super::auth::is_tchar;
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+
+/// `Content-Disposition` ( RFC 6266, RFC 7578 §4.2 ) with `filename*`
+/// of the extended notation ( RFC 8187 )
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Response, header};
+/// use whttp::util::ContentDisposition;
+///
+/// let res = Response::OK()
+///     .with(header::ContentDisposition, ContentDisposition::attachment().filename("€ rates.pdf"));
+/// assert_eq!(
+///     res.header(header::ContentDisposition),
+///     Some(r#"attachment; filename="_ rates.pdf"; filename*=UTF-8''%E2%82%AC%20rates.pdf"#)
+/// );
+///
+/// let upload = ContentDisposition::parse(r#"form-data; name="avatar"; filename="../../etc/passwd""#).unwrap();
+/// assert_eq!(upload.name.as_deref(), Some("avatar"));
+/// assert_eq!(upload.filename.as_deref(), Some("../../etc/passwd"));
+/// assert_eq!(upload.sanitized_filename().as_deref(), Some("passwd"));
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct ContentDisposition {
+    pub disposition: Disposition,
+    /// `name` of `form-data`
+    pub name:        Option<String>,
+    /// `filename*` if valid, or else `filename`, decoded and **unsanitized**
+    pub filename:    Option<String>,
+    /// other parameters with lowercase names, in order
+    pub extensions:  Vec<(String, String)>,
+}
+
+/// disposition type, case-insensitive
+#[derive(Clone, PartialEq, Debug)]
+pub enum Disposition {
+    Inline,
+    Attachment,
+    FormData,
+    /// unknown ones, which recipients should handle as `attachment`
+    Other(String),
+}
+
+impl Disposition {
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Inline     => "inline",
+            Self::Attachment => "attachment",
+            Self::FormData   => "form-data",
+            Self::Other(s)   => s,
+        }
+    }
+}
+
+impl ContentDisposition {
+    #[inline]
+    pub const fn inline() -> Self {
+        Self::of(Disposition::Inline)
+    }
+
+    #[inline]
+    pub const fn attachment() -> Self {
+        Self::of(Disposition::Attachment)
+    }
+
+    /// part of `multipart/form-data` for the field `name`
+    #[inline]
+    pub fn form_data(name: impl Into<String>) -> Self {
+        Self { name: Some(name.into()), ..Self::of(Disposition::FormData) }
+    }
+
+    const fn of(disposition: Disposition) -> Self {
+        Self { disposition, name: None, filename: None, extensions: Vec::new() }
+    }
+
+    /// Non-ASCII `filename` is sent by `filename*` together with
+    /// an ASCII fallback in `filename`.
+    #[inline]
+    pub fn filename(mut self, filename: impl Into<String>) -> Self {
+        self.filename = Some(filename.into());
+        self
+    }
+
+    /// Lenient for quoted-strings, accepting unescaped `\` in them as
+    /// some user agents send for `form-data`. `filename*` of charsets
+    /// other than `UTF-8` and `ISO-8859-1` is ignored.
+    pub fn parse(content_disposition: &str) -> Option<Self> {
+        let (disposition, mut rest) = match content_disposition.trim().split_once(';') {
+            Some((d, rest)) => (d.trim_end(), rest),
+            None => (content_disposition.trim(), "")
+        };
+        if disposition.is_empty() || !disposition.bytes().all(is_tchar) {return None}
+        let disposition = match &*disposition.to_ascii_lowercase() {
+            "inline"     => Disposition::Inline,
+            "attachment" => Disposition::Attachment,
+            "form-data"  => Disposition::FormData,
+            other        => Disposition::Other(other.into())
+        };
+
+        let mut this = Self::of(disposition);
+        let mut ext_filename = None;
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ';']);
+            if rest.is_empty() {break}
+
+            let (name, value, r) = parse_param(rest)?;
+            match &*name.to_ascii_lowercase() {
+                "name"      => this.name = Some(value),
+                "filename"  => this.filename = Some(value),
+                "filename*" => ext_filename = decode_ext_value(&value),
+                other       => this.extensions.push((other.into(), value))
+            }
+            rest = r;
+        }
+        if ext_filename.is_some() {
+            this.filename = ext_filename
+        }
+        Some(this)
+    }
+
+    /// [`filename`](Self::filename) made safe to be used as a local file name:
+    /// the last path segment, without control characters, characters
+    /// reserved on Windows, or leading/trailing dots and whitespace,
+    /// prefixed with `_` if it's a reserved name on Windows, and truncated
+    /// to 255 bytes. `None` if nothing remains.
+    pub fn sanitized_filename(&self) -> Option<String> {
+        let filename = self.filename.as_deref()?;
+        let basename = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+
+        let mut sanitized = basename
+            .chars()
+            .filter(|c| !c.is_control() && !matches!(c, '<' | '>' | ':' | '"' | '|' | '?' | '*'))
+            .collect::<String>()
+            .trim_matches(|c: char| c == '.' || c.is_whitespace())
+            .to_string();
+        if sanitized.is_empty() {return None}
+
+        let stem = sanitized.split('.').next().unwrap_or_default().trim_end();
+        if matches!(&*stem.to_ascii_uppercase(),
+            | "CON" | "PRN" | "AUX" | "NUL"
+            | "COM1" | "COM2" | "COM3" | "COM4" | "COM5" | "COM6" | "COM7" | "COM8" | "COM9"
+            | "LPT1" | "LPT2" | "LPT3" | "LPT4" | "LPT5" | "LPT6" | "LPT7" | "LPT8" | "LPT9"
+        ) {
+            sanitized.insert(0, '_');
+        }
+
+        if sanitized.len() > 255 {
+            let mut end = 255;
+            while !sanitized.is_char_boundary(end) {end -= 1}
+            sanitized.truncate(end);
+        }
+        Some(sanitized)
+    }
+}
+
+/// `token BWS "=" BWS ( token / quoted-string )` at the start of `s`, with the rest
+fn parse_param(s: &str) -> Option<(&str, String, &str)> {
+    let name_end = s.bytes().position(|b| !is_tchar(b)).unwrap_or(s.len());
+    if name_end == 0 {return None}
+    let (name, rest) = s.split_at(name_end);
+    let rest = rest.trim_start_matches([' ', '\t']).strip_prefix('=')?.trim_start_matches([' ', '\t']);
+
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let mut value = String::new();
+        let mut chars = quoted.char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' if matches!(chars.peek(), Some((_, '"' | '\\'))) => value.push(chars.next()?.1),
+                '"' => return Some((name, value, &quoted[i + 1..])),
+                _ => value.push(c)
+            }
+        }
+        None
+    } else {
+        let value_end = rest.bytes().position(|b| !is_tchar(b)).unwrap_or(rest.len());
+        if value_end == 0 {return None}
+        let (value, rest) = rest.split_at(value_end);
+        match rest.trim_start_matches([' ', '\t']).as_bytes().first() {
+            None | Some(b';') => Some((name, value.into(), rest)),
+            _ => None
+        }
+    }
+}
+
+/// `charset "'" [ language ] "'" value-chars` ( RFC 8187 §3.2.1 )
+pub(super) fn decode_ext_value(ext_value: &str) -> Option<String> {
+    let mut parts = ext_value.splitn(3, '\'');
+    let (charset, _language, encoded) = (parts.next()?, parts.next()?, parts.next()?);
+
+    let bytes = percent_decode_str(encoded).collect::<Vec<_>>();
+    if charset.eq_ignore_ascii_case("UTF-8") {
+        String::from_utf8(bytes).ok()
+    } else if charset.eq_ignore_ascii_case("ISO-8859-1") {
+        Some(bytes.into_iter().map(char::from).collect())
+    } else {
+        None
+    }
+}
+
+/// all but `attr-char` ( RFC 8187 §3.2.1 )
+pub(super) const NON_ATTR_CHAR: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'!').remove(b'#').remove(b'$').remove(b'&').remove(b'+')
+    .remove(b'-').remove(b'.').remove(b'^').remove(b'_').remove(b'`')
+    .remove(b'|').remove(b'~');
+
+const _: () = {
+    impl std::fmt::Display for ContentDisposition {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            fn write_quoted(f: &mut std::fmt::Formatter<'_>, name: &str, value: &str) -> std::fmt::Result {
+                write!(f, "; {name}=\"")?;
+                for c in value.chars() {
+                    match c {
+                        '"' | '\\'         => write!(f, "\\{c}")?,
+                        ' '..='~'          => write!(f, "{c}")?,
+                        _ if c.is_ascii()  => (),
+                        _                  => f.write_str("_")?
+                    }
+                }
+                f.write_str("\"")
+            }
+
+            f.write_str(self.disposition.as_str())?;
+            if let Some(name) = &self.name {
+                write_quoted(f, "name", name)?;
+            }
+            if let Some(filename) = &self.filename {
+                write_quoted(f, "filename", filename)?;
+                if !filename.bytes().all(|b| matches!(b, b' '..=b'~')) {
+                    write!(f, "; filename*=UTF-8''{}", utf8_percent_encode(filename, NON_ATTR_CHAR))?;
+                }
+            }
+            for (name, value) in &self.extensions {
+                if !value.is_empty() && value.bytes().all(is_tchar) {
+                    write!(f, "; {name}={value}")?
+                } else {
+                    write_quoted(f, name, value)?
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl From<ContentDisposition> for Value {
+        #[inline]
+        fn from(content_disposition: ContentDisposition) -> Self {
+            Value::from(content_disposition.to_string())
+        }
+    }
+
+    impl TypedHeader for ContentDisposition {
+        const HEADER: &'static Header = standard::ContentDisposition;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            ContentDisposition::parse(value)
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        /* RFC 6266 §5 */
+        assert_eq!(
+            ContentDisposition::parse("Attachment; filename=example.html"),
+            Some(ContentDisposition::attachment().filename("example.html"))
+        );
+        assert_eq!(
+            ContentDisposition::parse(r#"INLINE; FILENAME= "an example.html""#),
+            Some(ContentDisposition::inline().filename("an example.html"))
+        );
+        assert_eq!(
+            ContentDisposition::parse("attachment; filename*= UTF-8''%e2%82%ac%20rates"),
+            Some(ContentDisposition::attachment().filename("€ rates"))
+        );
+        assert_eq!(
+            ContentDisposition::parse(r#"attachment; filename="EURO rates"; filename*=utf-8''%e2%82%ac%20rates"#),
+            Some(ContentDisposition::attachment().filename("€ rates"))
+        );
+        /* filename* is preferred regardless of the order */
+        assert_eq!(
+            ContentDisposition::parse(r#"attachment; filename*=iso-8859-1'en'%A3%20rates; filename="GBP rates""#),
+            Some(ContentDisposition::attachment().filename("£ rates"))
+        );
+        /* unsupported filename* falls back to filename */
+        assert_eq!(
+            ContentDisposition::parse(r#"attachment; filename="rates"; filename*=Shift_JIS''%82%a0"#),
+            Some(ContentDisposition::attachment().filename("rates"))
+        );
+
+        assert_eq!(
+            ContentDisposition::parse(r#"form-data; name="files"; filename="C:\Users\me\a \"b\".txt""#),
+            Some(ContentDisposition::form_data("files").filename(r#"C:\Users\me\a "b".txt"#))
+        );
+        assert_eq!(
+            ContentDisposition::parse("x-custom;; size=42; note=\"a;b\""),
+            Some(ContentDisposition {
+                disposition: Disposition::Other("x-custom".into()),
+                name:        None,
+                filename:    None,
+                extensions:  vec![("size".into(), "42".into()), ("note".into(), "a;b".into())],
+            })
+        );
+
+        for invalid in ["", "; filename=a", "attachment; filename", "attachment; filename=\"a", "attachment; filename=a b", "attach ment"] {
+            assert_eq!(ContentDisposition::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(ContentDisposition::inline().to_string(), "inline");
+        assert_eq!(
+            ContentDisposition::attachment().filename(r#"say "hi".txt"#).to_string(),
+            r#"attachment; filename="say \"hi\".txt""#
+        );
+        assert_eq!(
+            ContentDisposition::form_data("file").filename("日本語.txt").to_string(),
+            r#"form-data; name="file"; filename="___.txt"; filename*=UTF-8''%E6%97%A5%E6%9C%AC%E8%AA%9E.txt"#
+        );
+        assert_eq!(
+            ContentDisposition::attachment().filename("a\r\nb").to_string(),
+            r#"attachment; filename="ab"; filename*=UTF-8''a%0D%0Ab"#
+        );
+
+        for cd in [
+            ContentDisposition::attachment().filename("€ rates.pdf"),
+            ContentDisposition::form_data("a\\b").filename("c\"d"),
+        ] {
+            assert_eq!(ContentDisposition::parse(&cd.to_string()), Some(cd));
+        }
+    }
+
+    #[test]
+    fn test_sanitized_filename() {
+        let sanitized = |filename: &str| ContentDisposition::attachment().filename(filename).sanitized_filename();
+
+        assert_eq!(sanitized("report.pdf").as_deref(), Some("report.pdf"));
+        assert_eq!(sanitized("../../etc/passwd").as_deref(), Some("passwd"));
+        assert_eq!(sanitized(r"C:\Users\me\photo.jpg").as_deref(), Some("photo.jpg"));
+        assert_eq!(sanitized("  .hidden. ").as_deref(), Some("hidden"));
+        assert_eq!(sanitized("a<b>c:d\"e|f?g*h\u{0}i\r\n.txt").as_deref(), Some("abcdefghi.txt"));
+        assert_eq!(sanitized("con.txt").as_deref(), Some("_con.txt"));
+        assert_eq!(sanitized("LPT1").as_deref(), Some("_LPT1"));
+        assert_eq!(sanitized("console.txt").as_deref(), Some("console.txt"));
+        assert_eq!(sanitized(&"あ".repeat(100)).map(|s| s.len()), Some(255));
+        for unsafe_ in ["", "..", "dir/", "...", "  ", "?*"] {
+            assert_eq!(sanitized(unsafe_), None, "{unsafe_}");
+        }
+        assert_eq!(ContentDisposition::inline().sanitized_filename(), None);
+    }
+}
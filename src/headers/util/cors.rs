@@ -0,0 +1,353 @@
+use crate::{Request, Response, Method, Value};
+use crate::headers::standard::{
+    Origin, Vary,
+    AccessControlAllowCredentials, AccessControlAllowHeaders, AccessControlAllowMethods,
+    AccessControlAllowOrigin, AccessControlExposeHeaders, AccessControlMaxAge,
+    AccessControlRequestHeaders, AccessControlRequestMethod,
+};
+
+/// CORS policy ( Fetch Standard §3.2 )
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Request, Response, Method, header};
+/// use whttp::util::Cors;
+///
+/// const CORS: Cors = Cors::new()
+///     .allow_origins(&["https://example.com"])
+///     .allow_methods(&[Method::GET, Method::POST])
+///     .allow_headers(&["Content-Type"])
+///     .allow_credentials()
+///     .max_age(600);
+///
+/// fn handle(req: &Request) -> Response {
+///     if let Some(preflight) = CORS.preflight(req) {
+///         return preflight
+///     }
+///
+///     let mut res = Response::OK().with_text("Hello, world!");
+///     CORS.apply(req, &mut res);
+///     res
+/// }
+///
+/// let res = handle(&Request::OPTIONS("/")
+///     .with(header::Origin, "https://example.com")
+///     .with(header::AccessControlRequestMethod, "POST"));
+/// assert_eq!(res.status(), whttp::Status::NoContent);
+/// assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://example.com"));
+/// assert_eq!(res.header(header::AccessControlAllowMethods), Some("GET, POST"));
+///
+/// let res = handle(&Request::GET("/")
+///     .with(header::Origin, "https://example.com"));
+/// assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://example.com"));
+/// assert_eq!(res.header(header::AccessControlAllowCredentials), Some("true"));
+/// assert_eq!(res.header(header::Vary), Some("Origin"));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Cors {
+    /// `None` for any origin
+    origins:        Option<&'static [&'static str]>,
+    methods:        &'static [Method],
+    /// `None` for any header ( the requested ones are allowed )
+    headers:        Option<&'static [&'static str]>,
+    expose_headers: &'static [&'static str],
+    credentials:    bool,
+    max_age:        Option<u32>,
+}
+
+impl Cors {
+    /// Allow any origin to `GET`, `HEAD` and `POST` without credentials
+    pub const fn new() -> Self {
+        Self {
+            origins:        None,
+            methods:        &[Method::GET, Method::HEAD, Method::POST],
+            headers:        Some(&[]),
+            expose_headers: &[],
+            credentials:    false,
+            max_age:        None,
+        }
+    }
+
+    /// Allow only `origins`, each of which is compared with `Origin` exactly
+    /// like `https://example.com` ( no trailing slash ).
+    pub const fn allow_origins(mut self, origins: &'static [&'static str]) -> Self {
+        self.origins = Some(origins);
+        self
+    }
+
+    pub const fn allow_methods(mut self, methods: &'static [Method]) -> Self {
+        self.methods = methods;
+        self
+    }
+
+    pub const fn allow_headers(mut self, headers: &'static [&'static str]) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Allow any request headers by mirroring `Access-Control-Request-Headers`,
+    /// which works also with credentials ( unlike `*` ).
+    pub const fn allow_any_headers(mut self) -> Self {
+        self.headers = None;
+        self
+    }
+
+    pub const fn expose_headers(mut self, headers: &'static [&'static str]) -> Self {
+        self.expose_headers = headers;
+        self
+    }
+
+    /// Allow credentials for the origins of `allow_origins`. Then
+    /// `Access-Control-Allow-Origin` is the requested origin instead of `*`,
+    /// as the wildcard is rejected by browsers for credentialed requests.
+    ///
+    /// Without `allow_origins`, no origin is allowed: echoing any origin
+    /// with credentials would let every site read credentialed responses.
+    pub const fn allow_credentials(mut self) -> Self {
+        self.credentials = true;
+        self
+    }
+
+    pub const fn max_age(mut self, seconds: u32) -> Self {
+        self.max_age = Some(seconds);
+        self
+    }
+}
+
+impl Default for Cors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Cors {
+    /// `Some` with the response for a preflight request
+    /// ( `OPTIONS` with `Origin` and `Access-Control-Request-Method` ),
+    /// `None` for the other requests.
+    ///
+    /// When the origin, method or headers are not allowed, the response
+    /// has no `Access-Control-Allow-*` headers and then the browser fails it.
+    pub fn preflight(&self, req: &Request) -> Option<Response> {
+        if req.method() != Method::OPTIONS {return None}
+        let origin = req.header(Origin)?;
+        let method = req.header(AccessControlRequestMethod)?;
+
+        let mut res = Response::NoContent();
+        append_vary(&mut res, "Origin, Access-Control-Request-Method, Access-Control-Request-Headers");
+
+        let Some(allow_origin) = self.allow_origin(origin) else {return Some(res)};
+
+        let method_allowed = Method::from_bytes(method.as_bytes())
+            .is_some_and(|m| self.methods.contains(&m));
+        let requested_headers = req.header(AccessControlRequestHeaders).unwrap_or_default();
+        let headers_allowed = match self.headers {
+            None => true,
+            Some(allowed) => requested_headers.split(',')
+                .map(str::trim)
+                .filter(|h| !h.is_empty())
+                .all(|h| allowed.iter().any(|a| a.eq_ignore_ascii_case(h)))
+        };
+        if !(method_allowed && headers_allowed) {return Some(res)}
+
+        res.set(AccessControlAllowOrigin, allow_origin)
+            .set(AccessControlAllowMethods, self.methods.iter()
+                .map(Method::as_str).collect::<Vec<_>>().join(", "));
+        match self.headers {
+            None => if !requested_headers.trim().is_empty() {
+                res.set(AccessControlAllowHeaders, requested_headers.trim().to_owned());
+            }
+            Some([]) => (),
+            Some(allowed) => {res.set(AccessControlAllowHeaders, allowed.join(", "));}
+        }
+        if self.credentials {
+            res.set(AccessControlAllowCredentials, "true");
+        }
+        if let Some(max_age) = self.max_age {
+            res.set(AccessControlMaxAge, max_age.to_string());
+        }
+        Some(res)
+    }
+
+    /// Add CORS headers to the response for an actual ( non-preflight ) request.
+    ///
+    /// `Vary: Origin` is added whenever the headers depend on `Origin`,
+    /// even for requests without `Origin` or from a disallowed origin,
+    /// so that caches don't mix up responses for different origins.
+    pub fn apply(&self, req: &Request, res: &mut Response) {
+        if self.origins.is_some() || self.credentials {
+            append_vary(res, "Origin");
+        }
+
+        let Some(origin) = req.header(Origin) else {return};
+        let Some(allow_origin) = self.allow_origin(origin) else {return};
+
+        res.set(AccessControlAllowOrigin, allow_origin);
+        if self.credentials {
+            res.set(AccessControlAllowCredentials, "true");
+        }
+        if !self.expose_headers.is_empty() {
+            res.set(AccessControlExposeHeaders, self.expose_headers.join(", "));
+        }
+    }
+
+    fn allow_origin(&self, origin: &str) -> Option<Value> {
+        match self.origins {
+            None if self.credentials => None,
+            None => Some(Value::new("*")),
+            Some(origins) => origins.contains(&origin).then(|| Value::from(origin.to_owned()))
+        }
+    }
+}
+
+fn append_vary(res: &mut Response, names: &'static str) {
+    let vary = match res.header(Vary) {
+        None => Value::new(names),
+        Some(v) if v.trim() == "*" => return,
+        Some(v) => {
+            let added = names.split(", ")
+                .filter(|n| !v.split(',').any(|e| e.trim().eq_ignore_ascii_case(n)))
+                .collect::<Vec<_>>();
+            if added.is_empty() {return}
+            Value::from(format!("{v}, {}", added.join(", ")))
+        }
+    };
+    res.set(Vary, vary);
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Status, header};
+
+    fn preflight(origin: &'static str, method: &'static str, headers: Option<&'static str>) -> Request {
+        let req = Request::OPTIONS("/")
+            .with(header::Origin, origin)
+            .with(header::AccessControlRequestMethod, method);
+        match headers {
+            Some(h) => req.with(header::AccessControlRequestHeaders, h),
+            None => req
+        }
+    }
+
+    #[test]
+    fn test_preflight_detection() {
+        let cors = Cors::new();
+        assert!(cors.preflight(&preflight("https://a.example", "GET", None)).is_some());
+        /* plain OPTIONS or no Origin: not a preflight */
+        assert!(cors.preflight(&Request::OPTIONS("/").with(header::Origin, "https://a.example")).is_none());
+        assert!(cors.preflight(&Request::OPTIONS("/").with(header::AccessControlRequestMethod, "GET")).is_none());
+        assert!(cors.preflight(&Request::GET("/")
+            .with(header::Origin, "https://a.example")
+            .with(header::AccessControlRequestMethod, "GET")).is_none());
+    }
+
+    #[test]
+    fn test_preflight() {
+        let cors = Cors::new()
+            .allow_origins(&["https://a.example", "null"])
+            .allow_methods(&[Method::GET, Method::PUT])
+            .allow_headers(&["Content-Type", "X-Token"])
+            .max_age(600);
+
+        let res = cors.preflight(&preflight("https://a.example", "PUT", Some("x-token, content-type"))).unwrap();
+        assert_eq!(res.status(), Status::NoContent);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://a.example"));
+        assert_eq!(res.header(header::AccessControlAllowMethods), Some("GET, PUT"));
+        assert_eq!(res.header(header::AccessControlAllowHeaders), Some("Content-Type, X-Token"));
+        assert_eq!(res.header(header::AccessControlMaxAge), Some("600"));
+        assert_eq!(res.header(header::AccessControlAllowCredentials), None);
+        assert_eq!(res.header(header::Vary), Some("Origin, Access-Control-Request-Method, Access-Control-Request-Headers"));
+
+        /* `null` origin only when explicitly listed */
+        let res = cors.preflight(&preflight("null", "GET", None)).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("null"));
+
+        /* origin is compared exactly */
+        for origin in ["https://a.example/", "https://A.example", "http://a.example", "https://a.example:443"] {
+            let res = cors.preflight(&preflight(origin, "GET", None)).unwrap();
+            assert_eq!(res.header(header::AccessControlAllowOrigin), None, "{origin}");
+        }
+
+        /* disallowed method or header: no Access-Control-Allow-* */
+        let res = cors.preflight(&preflight("https://a.example", "DELETE", None)).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        let res = cors.preflight(&preflight("https://a.example", "get", None)).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        let res = cors.preflight(&preflight("https://a.example", "GET", Some("X-Other"))).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::AccessControlAllowMethods), None);
+    }
+
+    #[test]
+    fn test_credentials() {
+        let cors = Cors::new()
+            .allow_origins(&["https://a.example"])
+            .allow_any_headers()
+            .allow_credentials();
+
+        /* never `*` with credentials */
+        let res = cors.preflight(&preflight("https://a.example", "POST", Some("X-Token"))).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://a.example"));
+        assert_eq!(res.header(header::AccessControlAllowCredentials), Some("true"));
+        assert_eq!(res.header(header::AccessControlAllowHeaders), Some("X-Token"));
+
+        let mut res = Response::OK();
+        cors.apply(&Request::GET("/").with(header::Origin, "https://a.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://a.example"));
+        assert_eq!(res.header(header::Vary), Some("Origin"));
+
+        /* unlisted origin: no Access-Control-Allow-* */
+        let res = cors.preflight(&preflight("https://b.example", "POST", None)).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::AccessControlAllowCredentials), None);
+
+        let mut res = Response::OK();
+        cors.apply(&Request::GET("/").with(header::Origin, "https://b.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::AccessControlAllowCredentials), None);
+        assert_eq!(res.header(header::Vary), Some("Origin"));
+
+        /* credentials without `allow_origins`: no origin is allowed */
+        let cors = Cors::new().allow_credentials();
+        let res = cors.preflight(&preflight("https://b.example", "GET", None)).unwrap();
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::AccessControlAllowCredentials), None);
+
+        let mut res = Response::OK();
+        cors.apply(&Request::GET("/").with(header::Origin, "https://b.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::AccessControlAllowCredentials), None);
+    }
+
+    #[test]
+    fn test_apply() {
+        /* any origin without credentials: `*` and no need of `Vary: Origin` */
+        let cors = Cors::new().expose_headers(&["X-Request-Id"]);
+        let mut res = Response::OK();
+        cors.apply(&Request::GET("/").with(header::Origin, "https://a.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("*"));
+        assert_eq!(res.header(header::AccessControlExposeHeaders), Some("X-Request-Id"));
+        assert_eq!(res.header(header::Vary), None);
+
+        /* allow-list: `Vary: Origin` even without Origin or from a disallowed one, merged to the existing */
+        let cors = Cors::new().allow_origins(&["https://a.example"]);
+
+        let mut res = Response::OK().with(header::Vary, "Accept-Encoding");
+        cors.apply(&Request::GET("/"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::Vary), Some("Accept-Encoding, Origin"));
+
+        let mut res = Response::OK().with(header::Vary, "origin");
+        cors.apply(&Request::GET("/").with(header::Origin, "https://b.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), None);
+        assert_eq!(res.header(header::Vary), Some("origin"));
+
+        let mut res = Response::OK().with(header::Vary, "*");
+        cors.apply(&Request::GET("/").with(header::Origin, "https://a.example"), &mut res);
+        assert_eq!(res.header(header::AccessControlAllowOrigin), Some("https://a.example"));
+        assert_eq!(res.header(header::Vary), Some("*"));
+    }
+}
@@ -0,0 +1,267 @@
+//! Content Security Policy ( CSP Level 3 )
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Response, header};
+//! use whttp::util::csp::{self, Csp, Nonce};
+//!
+//! let policy = Csp::new()
+//!     .default_src([csp::SELF])
+//!     .script_src([csp::SELF, csp::NONCE, csp::STRICT_DYNAMIC])
+//!     .img_src([csp::SELF, "data:", "https://cdn.example.com"])
+//!     .object_src([csp::NONE])
+//!     .upgrade_insecure_requests()
+//!     .report_to("csp-endpoint");
+//!
+//! let nonce = Nonce::new();
+//! let mut res = Response::OK().with_html(format!(r#"<script nonce="{nonce}">run()</script>"#));
+//! policy.apply(&mut res, Some(&nonce));
+//!
+//! assert_eq!(res.header(header::ContentSecurityPolicy).unwrap(), format!(
+//!     "default-src 'self'; script-src 'self' 'nonce-{nonce}' 'strict-dynamic'; \
+//!     img-src 'self' data: https://cdn.example.com; object-src 'none'; \
+//!     upgrade-insecure-requests; report-to csp-endpoint"
+//! ));
+//! ```
+
+use crate::{Response, Value};
+use crate::headers::standard::{ContentSecurityPolicy, ContentSecurityPolicyReportOnly};
+use std::borrow::Cow;
+
+pub const SELF:             &str = "'self'";
+pub const NONE:             &str = "'none'";
+pub const UNSAFE_INLINE:    &str = "'unsafe-inline'";
+pub const UNSAFE_EVAL:      &str = "'unsafe-eval'";
+pub const UNSAFE_HASHES:    &str = "'unsafe-hashes'";
+pub const STRICT_DYNAMIC:   &str = "'strict-dynamic'";
+pub const REPORT_SAMPLE:    &str = "'report-sample'";
+pub const WASM_UNSAFE_EVAL: &str = "'wasm-unsafe-eval'";
+/// placeholder replaced with `'nonce-<nonce>'` of each response, or
+/// removed when no nonce is given
+pub const NONCE:            &str = "'nonce'";
+
+/// per-response nonce, 128 bits in base64
+///
+/// read from the OS's CSPRNG via `getrandom`. Panics if the OS fails to
+/// provide random bytes.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Nonce(String);
+
+impl Nonce {
+    pub fn new() -> Self {
+        let mut bytes = [0; 16];
+        getrandom::fill(&mut bytes).expect("failed to get random bytes from the OS");
+        Self(super::base64::encode(bytes))
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Default for Nonce {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for Nonce {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// structured `Content-Security-Policy`, or `-Report-Only` by [`report_only`](Csp::report_only)
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Csp {
+    directives:  Vec<(Cow<'static, str>, Vec<Cow<'static, str>>)>,
+    report_only: bool,
+}
+
+macro_rules! directives {
+    ($( $method:ident = $name:literal ),* $(,)?) => {
+        impl Csp {$(
+            #[doc = concat!("`", $name, "`")]
+            #[inline]
+            pub fn $method(self, sources: impl IntoIterator<Item = impl Into<Cow<'static, str>>>) -> Self {
+                self.directive($name, sources)
+            }
+        )*}
+    };
+}
+directives! {
+    default_src     = "default-src",
+    script_src      = "script-src",
+    script_src_elem = "script-src-elem",
+    script_src_attr = "script-src-attr",
+    style_src       = "style-src",
+    style_src_elem  = "style-src-elem",
+    style_src_attr  = "style-src-attr",
+    img_src         = "img-src",
+    font_src        = "font-src",
+    connect_src     = "connect-src",
+    media_src       = "media-src",
+    object_src      = "object-src",
+    frame_src       = "frame-src",
+    child_src       = "child-src",
+    worker_src      = "worker-src",
+    manifest_src    = "manifest-src",
+    base_uri        = "base-uri",
+    form_action     = "form-action",
+    frame_ancestors = "frame-ancestors",
+    sandbox         = "sandbox",
+}
+
+impl Csp {
+    #[inline]
+    pub const fn new() -> Self {
+        Self { directives: Vec::new(), report_only: false }
+    }
+
+    /// Set the directive `name` to `sources`, replacing the existing one.
+    ///
+    /// Panics if `name` or any of `sources` is empty or contains
+    /// whitespace, `;`, `,` or non-ASCII characters.
+    pub fn directive(
+        mut self,
+        name:    impl Into<Cow<'static, str>>,
+        sources: impl IntoIterator<Item = impl Into<Cow<'static, str>>>,
+    ) -> Self {
+        fn valid(s: &str) -> bool {
+            !s.is_empty() && s.bytes().all(|b| matches!(b, b'!'..=b'~') && b != b';' && b != b',')
+        }
+
+        let name = name.into();
+        let sources = sources.into_iter().map(Into::into).collect::<Vec<_>>();
+        if !valid(&name) || !sources.iter().all(|s| valid(s)) {panic!("invalid CSP directive")}
+
+        match self.directives.iter_mut().find(|(n, _)| n.eq_ignore_ascii_case(&name)) {
+            Some(directive) => directive.1 = sources,
+            None => self.directives.push((name, sources))
+        }
+        self
+    }
+
+    /// `upgrade-insecure-requests`
+    #[inline]
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive("upgrade-insecure-requests", [] as [&str; 0])
+    }
+
+    /// `report-to` with the endpoint name of `Reporting-Endpoints`
+    #[inline]
+    pub fn report_to(self, endpoint: impl Into<Cow<'static, str>>) -> Self {
+        self.directive("report-to", [endpoint])
+    }
+
+    /// deprecated `report-uri`, for browsers not supporting `report-to`
+    #[inline]
+    pub fn report_uri(self, uri: impl Into<Cow<'static, str>>) -> Self {
+        self.directive("report-uri", [uri])
+    }
+
+    /// Send as `Content-Security-Policy-Report-Only` to only report violations.
+    #[inline]
+    pub fn report_only(mut self) -> Self {
+        self.report_only = true;
+        self
+    }
+
+    #[inline]
+    pub const fn is_report_only(&self) -> bool {
+        self.report_only
+    }
+
+    /// sources of the directive `name` ( case-insensitive )
+    pub fn sources(&self, name: &str) -> Option<&[Cow<'static, str>]> {
+        self.directives.iter()
+            .find_map(|(n, sources)| n.eq_ignore_ascii_case(name).then_some(&**sources))
+    }
+
+    /// Serialize with [`NONCE`] replaced by `nonce`, or removed for `None`.
+    pub fn render(&self, nonce: Option<&Nonce>) -> String {
+        let mut policy = String::new();
+        for (name, sources) in &self.directives {
+            if !policy.is_empty() {policy.push_str("; ")}
+            policy.push_str(name);
+            for source in sources {
+                if source == NONCE {
+                    let Some(nonce) = nonce else {continue};
+                    policy.push_str(" 'nonce-");
+                    policy.push_str(nonce.as_str());
+                    policy.push('\'');
+                } else {
+                    policy.push(' ');
+                    policy.push_str(source);
+                }
+            }
+        }
+        policy
+    }
+
+    /// Set the policy to `res` with `nonce`.
+    pub fn apply(&self, res: &mut Response, nonce: Option<&Nonce>) {
+        let header = if self.report_only {ContentSecurityPolicyReportOnly} else {ContentSecurityPolicy};
+        res.set(header, Value::from(self.render(nonce)));
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let policy = Csp::new()
+            .default_src([SELF])
+            .script_src([SELF, NONCE])
+            .style_src([SELF, UNSAFE_INLINE])
+            .directive("Script-Src", [NONE])
+            .sandbox(["allow-scripts", "allow-forms"])
+            .upgrade_insecure_requests();
+        assert_eq!(policy.render(None), "default-src 'self'; script-src 'none'; style-src 'self' 'unsafe-inline'; sandbox allow-scripts allow-forms; upgrade-insecure-requests");
+        assert_eq!(policy.sources("script-src").map(<[_]>::len), Some(1));
+
+        let policy = Csp::new().script_src([SELF, NONCE]);
+        assert_eq!(policy.render(None), "script-src 'self'");
+        let nonce = Nonce::new();
+        assert_eq!(policy.render(Some(&nonce)), format!("script-src 'self' 'nonce-{nonce}'"));
+    }
+
+    #[test]
+    fn test_apply() {
+        let nonce = Nonce::new();
+        let policy = Csp::new().default_src([SELF]).script_src([NONCE]).report_uri("/csp-reports");
+
+        let mut res = Response::OK();
+        policy.apply(&mut res, Some(&nonce));
+        assert_eq!(res.header(crate::header::ContentSecurityPolicy).unwrap(), format!(
+            "default-src 'self'; script-src 'nonce-{nonce}'; report-uri /csp-reports"
+        ));
+
+        let mut res = Response::OK();
+        policy.clone().report_only().apply(&mut res, None);
+        assert_eq!(res.header(crate::header::ContentSecurityPolicy), None);
+        assert_eq!(res.header(crate::header::ContentSecurityPolicyReportOnly), Some("default-src 'self'; script-src; report-uri /csp-reports"));
+    }
+
+    #[test]
+    fn test_nonce() {
+        let (a, b) = (Nonce::new(), Nonce::new());
+        assert_ne!(a, b);
+        assert_eq!(a.as_str().len(), 24);
+        assert_eq!(super::super::base64::decode(a.as_str()).map(|bytes| bytes.len()), Some(16));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_source() {
+        let _ = Csp::new().script_src(["'self'; object-src *"]);
+    }
+}
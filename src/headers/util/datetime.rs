@@ -4,9 +4,16 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 const IMF_FIXDATE_LEN: usize = str::len("Sun, 06 Nov 1994 08:49:37 GMT");
 
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub struct IMFfixdate([u8; IMF_FIXDATE_LEN]);
 
 const _: () = {
+    impl std::fmt::Debug for IMFfixdate {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self)
+        }
+    }
+
     impl Into<crate::headers::Value> for IMFfixdate {
         #[inline]
         fn into(self) -> crate::headers::Value {
@@ -14,6 +21,24 @@ const _: () = {
         }
     }
 
+    /// chronological order, and then the weekday to be consistent with `Eq`
+    /// for dates of the same instant with wrong weekdays
+    impl Ord for IMFfixdate {
+        fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+            fn key(date: &IMFfixdate) -> (&[u8], usize, &[u8], &[u8], &[u8]) {
+                let month = SHORT_MONTHS.iter().position(|m| &date.0[8..11] == *m).unwrap_or_default();
+                (&date.0[12..16], month, &date.0[5..7], &date.0[17..25], &date.0[0..3])
+            }
+            key(self).cmp(&key(other))
+        }
+    }
+    impl PartialOrd for IMFfixdate {
+        #[inline]
+        fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
     impl std::ops::Deref for IMFfixdate {
         type Target = str;
         #[inline]
@@ -23,16 +48,44 @@ const _: () = {
     }
 };
 
+const SHORT_WEEKDAYS: [&[u8; 3]; 7 ] = [b"Sun", b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat"];
+const SHORT_MONTHS:   [&[u8; 3]; 12] = [b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec"];
+
 impl IMFfixdate {
+    /// Parse `Sun, 06 Nov 1994 08:49:37 GMT` format.
+    /// 
+    /// This doesn't accept obsolete RFC 850 or asctime formats.
+    pub fn parse(s: &str) -> Option<Self> {
+        let bytes: [u8; IMF_FIXDATE_LEN] = s.as_bytes().try_into().ok()?;
+
+        let digits = |range: std::ops::Range<usize>| bytes[range].iter()
+            .try_fold(0u32, |n, b| b.is_ascii_digit().then(|| 10*n + (b - b'0') as u32));
+
+        let valid =
+            SHORT_WEEKDAYS.iter().any(|w| &bytes[0..3] == *w) &&
+            &bytes[3..5] == b", " &&
+            digits(5..7).is_some_and(|day| (1..=31).contains(&day)) &&
+            bytes[7] == b' ' &&
+            SHORT_MONTHS.iter().any(|m| &bytes[8..11] == *m) &&
+            bytes[11] == b' ' &&
+            digits(12..16).is_some() &&
+            bytes[16] == b' ' &&
+            digits(17..19).is_some_and(|hour| hour < 24) &&
+            bytes[19] == b':' &&
+            digits(20..22).is_some_and(|min| min < 60) &&
+            bytes[22] == b':' &&
+            digits(23..25).is_some_and(|sec| sec <= 60) &&
+            &bytes[25..] == b" GMT";
+
+        valid.then_some(Self(bytes))
+    }
+
     #[inline]
     pub fn now() -> Self {
         Self::of_unix_timestamp(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs())
     }
     
     pub fn of_unix_timestamp(secs: u64) -> Self {
-        const SHORT_WEEKDAYS: [&[u8; 3]; 7 ] = [b"Sun", b"Mon", b"Tue", b"Wed", b"Thu", b"Fri", b"Sat"];
-        const SHORT_MONTHS:   [&[u8; 3]; 12] = [b"Jan", b"Feb", b"Mar", b"Apr", b"May", b"Jun", b"Jul", b"Aug", b"Sep", b"Oct", b"Nov", b"Dec"];
-        
         let mut fmt = [std::mem::MaybeUninit::<u8>::uninit(); IMF_FIXDATE_LEN];
         let mut i = 0;
 
@@ -377,6 +430,41 @@ impl Weekday {
 
 
 #[cfg(test)] mod test {
+    #[test] fn test_parse() {
+        use super::IMFfixdate;
+
+        let date = IMFfixdate::parse("Sun, 06 Nov 1994 08:49:37 GMT").unwrap();
+        assert_eq!(&*date, "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(date, IMFfixdate::of_unix_timestamp(784111777));
+
+        assert!(IMFfixdate::parse("Sunday, 06-Nov-94 08:49:37 GMT").is_none());
+        assert!(IMFfixdate::parse("Sun Nov  6 08:49:37 1994").is_none());
+        assert!(IMFfixdate::parse("Sun, 06 Nov 1994 08:49:37 UTC").is_none());
+        assert!(IMFfixdate::parse("Sun, 36 Nov 1994 08:49:37 GMT").is_none());
+    }
+
+    #[test] fn test_ord() {
+        use super::IMFfixdate;
+
+        for (earlier, later) in [
+            (784111777, 784111778),
+            (784111777, 786790177/* Dec */),
+            (1704067199/* 2023-12-31 */, 1704067200/* 2024-01-01 */),
+        ] {
+            assert!(IMFfixdate::of_unix_timestamp(earlier) < IMFfixdate::of_unix_timestamp(later));
+        }
+
+        /* same instant with a wrong weekday: not equal, so not `Equal` */
+        let (correct, wrong) = (
+            IMFfixdate::parse("Sun, 06 Nov 1994 08:49:37 GMT").unwrap(),
+            IMFfixdate::parse("Mon, 06 Nov 1994 08:49:37 GMT").unwrap(),
+        );
+        assert_ne!(correct, wrong);
+        assert_ne!(correct.cmp(&wrong), std::cmp::Ordering::Equal);
+        assert_eq!(correct.cmp(&wrong), wrong.cmp(&correct).reverse());
+        assert!(correct < IMFfixdate::parse("Sat, 06 Nov 1994 08:49:38 GMT").unwrap());
+    }
+
     #[test] fn test_now() {
         fn correct_now() -> String {
             let mut output_bytes = std::process::Command::new("/usr/bin/date")
@@ -0,0 +1,497 @@
+//! HTTP Digest access authentication ( RFC 7616 )
+//!
+//! Only `qop=auth` is supported. Nonces are stateless : each one carries
+//! its issue time signed by the server's secret, so any server sharing
+//! the secret can verify it, but a nonce may be replayed until it expires.
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response, Method, header};
+//! use whttp::util::auth::Challenge;
+//! use whttp::util::digest::{Digest, DigestClient};
+//!
+//! let digest = Digest::new("http-auth@example.org");
+//!
+//! fn handle(digest: &Digest, req: &Request) -> Response {
+//!     match digest.verify(req, |user| (user == "Mufasa").then_some("Circle of Life")) {
+//!         Ok(user) => Response::OK().with_text(format!("Hello, {user}!")),
+//!         Err(rejection) => digest.unauthorized(rejection)
+//!     }
+//! }
+//!
+//! let res = handle(&digest, &Request::GET("/dir/index.html"));
+//! assert_eq!(res.status(), whttp::Status::Unauthorized);
+//!
+//! let challenges = Challenge::parse_list(res.header(header::WWWAuthenticate).unwrap()).unwrap();
+//! let mut client = DigestClient::new("Mufasa", "Circle of Life");
+//! let credentials = client.authorize(&challenges[0], Method::GET, "/dir/index.html").unwrap();
+//!
+//! let res = handle(&digest, &Request::GET("/dir/index.html").with(header::Authorization, credentials));
+//! assert_eq!(res.status(), whttp::Status::OK);
+//! ```
+
+use super::auth::{Challenge, Credentials};
+use super::{md5::md5, sha256::{sha256, hmac_sha256}};
+use crate::{Request, Response, Method};
+use crate::headers::standard::WWWAuthenticate;
+use std::borrow::Cow;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// `algorithm` of Digest, `MD5` when absent
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    MD5,
+    SHA256,
+}
+
+impl Algorithm {
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MD5    => "MD5",
+            Self::SHA256 => "SHA-256",
+        }
+    }
+
+    /// case-insensitive, `None` for unsupported ones including `-sess` variants
+    pub fn parse(algorithm: &str) -> Option<Self> {
+        if algorithm.eq_ignore_ascii_case("MD5") {
+            Some(Self::MD5)
+        } else if algorithm.eq_ignore_ascii_case("SHA-256") {
+            Some(Self::SHA256)
+        } else {
+            None
+        }
+    }
+
+    /// lowercase hex of the hash of `data`
+    fn hex(&self, data: &str) -> String {
+        let hash = match self {
+            Self::MD5    => md5(data.as_bytes()).to_vec(),
+            Self::SHA256 => sha256(data.as_bytes()).to_vec(),
+        };
+        hash.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// `response` of `qop=auth` ( RFC 7616 §3.4.1 )
+    #[allow(clippy::too_many_arguments)]
+    fn response(&self,
+        username: &str, realm: &str, password: &str,
+        method: Method, uri: &str,
+        nonce: &str, nc: &str, cnonce: &str,
+    ) -> String {
+        let ha1 = self.hex(&format!("{username}:{realm}:{password}"));
+        let ha2 = self.hex(&format!("{}:{uri}", method.as_str()));
+        self.hex(&format!("{ha1}:{nonce}:{nc}:{cnonce}:auth:{ha2}"))
+    }
+}
+
+/// why [`Digest::verify`] rejected the request
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Rejection {
+    /// no Digest credentials
+    Missing,
+    /// malformed credentials, unknown user or wrong password
+    Invalid,
+    /// correct credentials with an expired nonce
+    Stale,
+}
+
+/// server side of Digest authentication
+#[derive(Clone)]
+pub struct Digest {
+    realm:     Cow<'static, str>,
+    algorithm: Algorithm,
+    secret:    Vec<u8>,
+    nonce_ttl: u64,
+}
+
+impl Digest {
+    /// `SHA-256` with nonces valid for 5 minutes, signed by a 256-bit
+    /// secret read from the OS's CSPRNG per `Digest`.
+    /// Panics if the OS fails to provide random bytes.
+    pub fn new(realm: impl Into<Cow<'static, str>>) -> Self {
+        let mut secret = vec![0; 32];
+        getrandom::fill(&mut secret).expect("failed to get random bytes from the OS");
+        Self {
+            realm:     realm.into(),
+            algorithm: Algorithm::SHA256,
+            secret,
+            nonce_ttl: 300,
+        }
+    }
+
+    #[inline]
+    pub fn algorithm(mut self, algorithm: Algorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    /// secret to sign nonces, to be shared by the servers behind the same realm
+    #[inline]
+    pub fn secret(mut self, secret: impl AsRef<[u8]>) -> Self {
+        self.secret = secret.as_ref().to_vec();
+        self
+    }
+
+    /// lifetime of nonces in seconds
+    #[inline]
+    pub fn nonce_ttl(mut self, secs: u64) -> Self {
+        self.nonce_ttl = secs;
+        self
+    }
+
+    /// `WWW-Authenticate` challenge with a fresh nonce
+    #[inline]
+    pub fn challenge(&self) -> Challenge<'static> {
+        self.challenge_at(now())
+    }
+
+    /// `401 Unauthorized` with [`challenge`](Self::challenge), marked
+    /// `stale` for [`Rejection::Stale`]
+    pub fn unauthorized(&self, rejection: Rejection) -> Response {
+        let mut challenge = self.challenge();
+        if rejection == Rejection::Stale {
+            challenge = challenge.with_param("stale", "true");
+        }
+        Response::Unauthorized().with(WWWAuthenticate, challenge)
+    }
+
+    /// Verify `Authorization` of `req` against its method and
+    /// `raw_path` ( with query ), where `password_of` returns the
+    /// password of the user. Returns the username on success.
+    ///
+    /// `userhash` and `username*` are not supported.
+    #[inline]
+    pub fn verify<'req, P: AsRef<str>>(&self,
+        req:         &'req Request,
+        password_of: impl FnOnce(&str) -> Option<P>,
+    ) -> Result<Cow<'req, str>, Rejection> {
+        self.verify_at(req, password_of, now())
+    }
+
+    fn challenge_at(&self, timestamp: u64) -> Challenge<'static> {
+        Challenge::new("Digest")
+            .with_realm(self.realm.clone())
+            .with_param("qop", "auth")
+            .with_param("algorithm", self.algorithm.as_str())
+            .with_param("nonce", self.nonce(timestamp))
+    }
+
+    /// 16 hex digits of `timestamp` followed by 32 of its HMAC-SHA-256
+    /// signature by the secret
+    fn nonce(&self, timestamp: u64) -> String {
+        let signature = hmac_sha256(&self.secret, &timestamp.to_be_bytes())
+            .iter().take(16).map(|b| format!("{b:02x}")).collect::<String>();
+        format!("{timestamp:016x}{signature}")
+    }
+
+    /// issued timestamp of `nonce` if it's signed by `self`
+    fn timestamp_of(&self, nonce: &str) -> Option<u64> {
+        if nonce.len() != 48 {return None}
+        let timestamp = u64::from_str_radix(nonce.get(..16)?, 16).ok()?;
+        constant_time_eq(nonce, &self.nonce(timestamp)).then_some(timestamp)
+    }
+
+    fn verify_at<'req, P: AsRef<str>>(&self,
+        req:         &'req Request,
+        password_of: impl FnOnce(&str) -> Option<P>,
+        now:         u64,
+    ) -> Result<Cow<'req, str>, Rejection> {
+        let Some(Credentials::Other { token68: None, params, .. }) = req.authorization()
+            .filter(|c| c.scheme().eq_ignore_ascii_case("Digest")) else {
+            return Err(Rejection::Missing)
+        };
+
+        let mut username = None;
+        let (mut realm, mut uri, mut nonce, mut nc, mut cnonce, mut qop, mut response) = Default::default();
+        let mut algorithm = Algorithm::MD5;
+        for (name, value) in params {
+            match &*name.to_ascii_lowercase() {
+                "username"  => username  = Some(value),
+                "realm"     => realm     = Some(value),
+                "uri"       => uri       = Some(value),
+                "nonce"     => nonce     = Some(value),
+                "nc"        => nc        = Some(value),
+                "cnonce"    => cnonce    = Some(value),
+                "qop"       => qop       = Some(value),
+                "response"  => response  = Some(value),
+                "algorithm" => algorithm = Algorithm::parse(&value).ok_or(Rejection::Invalid)?,
+                "userhash" if value.eq_ignore_ascii_case("true") => return Err(Rejection::Invalid),
+                _ => ()
+            }
+        }
+        let (
+            Some(username), Some(realm), Some(uri), Some(nonce), Some(nc), Some(cnonce), Some(qop), Some(response)
+        ) = (username, realm, uri, nonce, nc, cnonce, qop, response) else {
+            return Err(Rejection::Invalid)
+        };
+
+        let request_target = match req.query() {
+            Some(query) => Cow::Owned(format!("{}?{query}", req.raw_path())),
+            None => Cow::Borrowed(req.raw_path())
+        };
+        if !(
+            realm == self.realm &&
+            algorithm == self.algorithm &&
+            uri == request_target &&
+            qop == "auth" &&
+            nc.len() == 8 && nc.bytes().all(|b| b.is_ascii_hexdigit()) &&
+            !cnonce.is_empty()
+        ) {
+            return Err(Rejection::Invalid)
+        }
+        let issued = self.timestamp_of(&nonce).ok_or(Rejection::Invalid)?;
+
+        let password = password_of(&username).ok_or(Rejection::Invalid)?;
+        let expected = algorithm.response(
+            &username, &realm, password.as_ref(),
+            req.method(), &uri,
+            &nonce, &nc, &cnonce,
+        );
+        if !constant_time_eq(&response.to_ascii_lowercase(), &expected) {
+            return Err(Rejection::Invalid)
+        }
+
+        /* stale only when the credentials are otherwise valid ( RFC 7616 §3.3 ) */
+        if now < issued || now - issued > self.nonce_ttl {
+            return Err(Rejection::Stale)
+        }
+        Ok(username)
+    }
+}
+
+impl std::fmt::Debug for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Digest")
+            .field("realm", &self.realm)
+            .field("algorithm", &self.algorithm)
+            .field("nonce_ttl", &self.nonce_ttl)
+            .finish_non_exhaustive()
+    }
+}
+
+/// client side of Digest authentication, counting `nc` per nonce
+#[derive(Clone, Debug)]
+pub struct DigestClient {
+    username: Cow<'static, str>,
+    password: Cow<'static, str>,
+    nonce:    Option<String>,
+    nc:       u32,
+}
+
+impl DigestClient {
+    pub fn new(username: impl Into<Cow<'static, str>>, password: impl Into<Cow<'static, str>>) -> Self {
+        Self { username: username.into(), password: password.into(), nonce: None, nc: 0 }
+    }
+
+    /// `Authorization` responding to `challenge` for `method` and `uri`
+    /// ( the request-target like `/path?query` ).
+    ///
+    /// `None` if `challenge` is not Digest offering `qop=auth` with a
+    /// supported algorithm, or the username is not ASCII.
+    /// Panics if the OS fails to provide random bytes for `cnonce`.
+    #[inline]
+    pub fn authorize(&mut self, challenge: &Challenge<'_>, method: Method, uri: &str) -> Option<Credentials<'static>> {
+        let mut cnonce = [0; 16];
+        getrandom::fill(&mut cnonce).expect("failed to get random bytes from the OS");
+        let cnonce = cnonce.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        self.authorize_with_cnonce(challenge, method, uri, cnonce)
+    }
+
+    fn authorize_with_cnonce(&mut self,
+        challenge: &Challenge<'_>,
+        method:    Method,
+        uri:       &str,
+        cnonce:    String,
+    ) -> Option<Credentials<'static>> {
+        if !challenge.scheme.eq_ignore_ascii_case("Digest") || !self.username.is_ascii() {
+            return None
+        }
+        let realm = challenge.realm()?;
+        let nonce = challenge.param("nonce")?;
+        let algorithm = challenge.param("algorithm").map_or(Some(Algorithm::MD5), Algorithm::parse)?;
+        if !challenge.param("qop")?.split(',').any(|qop| qop.trim() == "auth") {
+            return None
+        }
+        let userhash = challenge.param("userhash").is_some_and(|u| u.eq_ignore_ascii_case("true"));
+
+        if self.nonce.as_deref() != Some(nonce) {
+            self.nonce = Some(nonce.into());
+            self.nc = 0;
+        }
+        self.nc += 1;
+        let nc = format!("{:08x}", self.nc);
+
+        let response = algorithm.response(
+            &self.username, realm, &self.password,
+            method, uri,
+            nonce, &nc, &cnonce,
+        );
+        let username = if userhash {
+            algorithm.hex(&format!("{}:{realm}", self.username))
+        } else {
+            self.username.to_string()
+        };
+
+        let mut params: Vec<(&'static str, Cow<'static, str>)> = vec![
+            ("username",  username.into()),
+            ("realm",     realm.to_owned().into()),
+            ("uri",       uri.to_owned().into()),
+            ("algorithm", algorithm.as_str().into()),
+            ("nonce",     nonce.to_owned().into()),
+            ("nc",        nc.into()),
+            ("cnonce",    cnonce.into()),
+            ("qop",       "auth".into()),
+            ("response",  response.into()),
+        ];
+        if let Some(opaque) = challenge.param("opaque") {
+            params.push(("opaque", opaque.to_owned().into()));
+        }
+        if userhash {
+            params.push(("userhash", "true".into()));
+        }
+        Some(Credentials::Other { scheme: "Digest", token68: None, params })
+    }
+}
+
+#[inline]
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs())
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header;
+
+    /* RFC 7616 §3.9.1 */
+    const REALM:  &str = "http-auth@example.org";
+    const NONCE:  &str = "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v";
+    const CNONCE: &str = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+    const OPAQUE: &str = "FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS";
+
+    #[test]
+    fn test_rfc_example() {
+        for (algorithm, response) in [
+            ("MD5",     "8ca523f5e9506fed4657c9700eebdbec"),
+            ("SHA-256", "753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1"),
+        ] {
+            let www_authenticate = format!(
+                r#"Digest realm="{REALM}", qop="auth, auth-int", algorithm={algorithm}, nonce="{NONCE}", opaque="{OPAQUE}""#
+            );
+            let challenge = Challenge::parse_list(&www_authenticate).unwrap().remove(0);
+
+            let credentials = DigestClient::new("Mufasa", "Circle of Life")
+                .authorize_with_cnonce(&challenge, Method::GET, "/dir/index.html", CNONCE.into())
+                .unwrap();
+            assert_eq!(credentials.param("response"), Some(response));
+            assert_eq!(credentials.to_string(), format!(
+                r#"Digest username="Mufasa", realm="{REALM}", uri="/dir/index.html", algorithm={algorithm}, nonce="{NONCE}", nc=00000001, cnonce="{CNONCE}", qop=auth, response="{response}", opaque="{OPAQUE}""#
+            ));
+        }
+    }
+
+    #[test]
+    fn test_client() {
+        let mut client = DigestClient::new("Mufasa", "Circle of Life");
+        let challenge = Challenge::new("Digest")
+            .with_realm(REALM)
+            .with_param("qop", "auth")
+            .with_param("nonce", NONCE);
+
+        let first = client.authorize(&challenge, Method::GET, "/").unwrap();
+        assert_eq!(first.param("algorithm"), Some("MD5"));
+        assert_eq!(first.param("nc"), Some("00000001"));
+        let second = client.authorize(&challenge, Method::GET, "/").unwrap();
+        assert_eq!(second.param("nc"), Some("00000002"));
+        assert_ne!(first.param("cnonce"), second.param("cnonce"));
+
+        let renewed = client.authorize(&challenge.clone().with_param("nonce", "renewed"), Method::GET, "/").unwrap();
+        assert_eq!(renewed.param("nc"), Some("00000001"));
+
+        /* RFC 7616 §3.4.4 */
+        let hashed = client.authorize(&challenge.clone().with_param("userhash", "true"), Method::GET, "/").unwrap();
+        assert_eq!(hashed.param("username"), Some(&*Algorithm::MD5.hex(&format!("Mufasa:{REALM}"))));
+
+        assert!(client.authorize(&Challenge::basic(REALM), Method::GET, "/").is_none());
+        assert!(client.authorize(&challenge.clone().with_param("qop", "auth-int"), Method::GET, "/").is_none());
+        assert!(client.authorize(&challenge.clone().with_param("algorithm", "SHA-512-256"), Method::GET, "/").is_none());
+    }
+
+    #[test]
+    fn test_verify() {
+        const NOW: u64 = 1_700_000_000;
+
+        let digest = Digest::new(REALM).secret("secret");
+        let password_of = |user: &str| (user == "Mufasa").then_some("Circle of Life");
+        let challenge = digest.challenge_at(NOW);
+        assert_eq!(challenge.param("algorithm"), Some("SHA-256"));
+        assert_eq!(challenge.to_string(), format!(
+            r#"Digest realm="{REALM}", qop="auth", algorithm=SHA-256, nonce="{}""#, digest.nonce(NOW)
+        ));
+
+        let request = |user: &str, password: &'static str, method: Method, uri: &str, path: &'static str| {
+            let credentials = DigestClient::new(user.to_owned(), password)
+                .authorize(&challenge, method, uri)
+                .unwrap();
+            Request::of(Method::GET, path).with(header::Authorization, credentials)
+        };
+
+        let req = request("Mufasa", "Circle of Life", Method::GET, "/dir/index.html", "/dir/index.html");
+        assert_eq!(digest.verify_at(&req, password_of, NOW + 10).as_deref(), Ok("Mufasa"));
+        assert_eq!(digest.verify_at(&req, password_of, NOW + 301), Err(Rejection::Stale));
+
+        let req = request("Mufasa", "Circle of Life", Method::GET, "/search?q=lion", "/search")
+            .with_query("q", "lion");
+        assert_eq!(digest.verify_at(&req, password_of, NOW).as_deref(), Ok("Mufasa"));
+
+        for req in [
+            request("Mufasa", "Circle of Death", Method::GET, "/", "/"),
+            request("Simba", "Circle of Life", Method::GET, "/", "/"),
+            request("Mufasa", "Circle of Life", Method::GET, "/other", "/"),
+            request("Mufasa", "Circle of Life", Method::POST, "/", "/"),
+        ] {
+            assert_eq!(digest.verify_at(&req, password_of, NOW), Err(Rejection::Invalid));
+        }
+        /* stale is reported only for otherwise valid credentials */
+        let req = request("Mufasa", "Circle of Death", Method::GET, "/", "/");
+        assert_eq!(digest.verify_at(&req, password_of, NOW + 301), Err(Rejection::Invalid));
+
+        /* nonce from another secret */
+        let req = request("Mufasa", "Circle of Life", Method::GET, "/", "/");
+        assert_eq!(Digest::new(REALM).verify_at(&req, password_of, NOW), Err(Rejection::Invalid));
+        /* algorithm differing from the challenge */
+        assert_eq!(digest.clone().algorithm(Algorithm::MD5).verify_at(&req, password_of, NOW), Err(Rejection::Invalid));
+
+        assert_eq!(digest.verify_at(&Request::GET("/"), password_of, NOW), Err(Rejection::Missing));
+        assert_eq!(digest.verify_at(
+            &Request::GET("/").with(header::Authorization, Credentials::basic("Mufasa", "Circle of Life")),
+            password_of, NOW
+        ), Err(Rejection::Missing));
+    }
+
+    #[test]
+    fn test_unauthorized() {
+        let digest = Digest::new(REALM);
+        let res = digest.unauthorized(Rejection::Stale);
+        assert_eq!(res.status(), crate::Status::Unauthorized);
+
+        let challenges = Challenge::parse_list(res.header(header::WWWAuthenticate).unwrap()).unwrap();
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].realm(), Some(REALM));
+        assert_eq!(challenges[0].param("stale"), Some("true"));
+        assert!(digest.timestamp_of(challenges[0].param("nonce").unwrap()).is_some());
+
+        let res = digest.unauthorized(Rejection::Invalid);
+        let challenges = Challenge::parse_list(res.header(header::WWWAuthenticate).unwrap()).unwrap();
+        assert_eq!(challenges[0].param("stale"), None);
+    }
+}
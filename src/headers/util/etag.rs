@@ -0,0 +1,200 @@
+use crate::headers::{Header, Value, TypedHeader, standard};
+use std::borrow::Cow;
+
+/// Entity tag ( RFC 9110 §8.8.3 ): `"<opaque-tag>"` or `W/"<opaque-tag>"`
+///
+/// *example.rs*
+/// ```
+/// use whttp::util::ETag;
+///
+/// let etag = ETag::of_payload(b"Hello, world!");
+/// assert!(!etag.is_weak());
+///
+/// let weak = ETag::parse(r#"W/"xyzzy""#).unwrap();
+/// assert!(weak.weak_eq(&ETag::strong("xyzzy")));
+/// assert!(!weak.strong_eq(&ETag::strong("xyzzy")));
+/// assert_eq!(weak.to_string(), r#"W/"xyzzy""#);
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ETag {
+    weak: bool,
+    tag:  Cow<'static, str>,
+}
+
+#[inline(always)]
+const fn is_etagc(b: u8) -> bool {
+    matches!(b, 0x21 | 0x23..=0x7E)
+}
+
+impl ETag {
+    /// Panics if `tag` contains `"`, whitespace or non-ASCII characters.
+    pub fn strong(tag: impl Into<Cow<'static, str>>) -> Self {
+        let tag = tag.into();
+        if !tag.bytes().all(is_etagc) {panic!("invalid entity tag")}
+        Self { weak: false, tag }
+    }
+
+    /// Panics if `tag` contains `"`, whitespace or non-ASCII characters.
+    pub fn weak(tag: impl Into<Cow<'static, str>>) -> Self {
+        Self { weak: true, ..Self::strong(tag) }
+    }
+
+    /// strong entity tag from the length and a 64-bit FNV-1a hash of `payload`
+    pub fn of_payload(payload: &[u8]) -> Self {
+        let hash = payload.iter().fold(0xcbf29ce484222325_u64, |hash, b| {
+            (hash ^ *b as u64).wrapping_mul(0x100000001b3)
+        });
+        Self { weak: false, tag: Cow::Owned(format!("{:x}-{hash:016x}", payload.len())) }
+    }
+
+    pub fn parse(etag: &str) -> Option<Self> {
+        match parse_one(etag.trim()) {
+            Some((etag, "")) => Some(etag),
+            _ => None
+        }
+    }
+
+    #[inline]
+    pub const fn is_weak(&self) -> bool {
+        self.weak
+    }
+
+    /// `opaque-tag` without quotes
+    #[inline]
+    pub fn tag(&self) -> &str {
+        &self.tag
+    }
+
+    /// both are strong and have the same opaque-tag
+    #[inline]
+    pub fn strong_eq(&self, other: &Self) -> bool {
+        !self.weak && !other.weak && self.tag == other.tag
+    }
+
+    /// same opaque-tag regardless of weakness
+    #[inline]
+    pub fn weak_eq(&self, other: &Self) -> bool {
+        self.tag == other.tag
+    }
+}
+
+/// parse an entity tag at the start of `s` and return it with the rest
+fn parse_one(s: &str) -> Option<(ETag, &str)> {
+    let (weak, s) = match s.strip_prefix("W/") {
+        Some(s) => (true, s),
+        None => (false, s)
+    };
+    let s = s.strip_prefix('"')?;
+    let end = s.bytes().position(|b| !is_etagc(b))?;
+    if s.as_bytes()[end] != b'"' {return None}
+    Some((ETag { weak, tag: Cow::Owned(s[..end].into()) }, &s[end + 1..]))
+}
+
+/// `*` or a list of entity tags like `If-Match` and `If-None-Match`
+#[derive(Clone, PartialEq, Debug)]
+pub enum ETags {
+    Any,
+    List(Vec<ETag>),
+}
+impl ETags {
+    /// Invalid members of the list are skipped.
+    pub fn parse(etags: &str) -> Self {
+        let mut rest = etags.trim();
+        if rest == "*" {return Self::Any}
+
+        let mut list = Vec::new();
+        while !rest.is_empty() {
+            match parse_one(rest) {
+                Some((etag, r)) => {list.push(etag); rest = r}
+                None => rest = rest.find(',').map_or("", |i| &rest[i..])
+            }
+            rest = rest.trim_start_matches([' ', '\t', ',']);
+        }
+        Self::List(list)
+    }
+}
+
+const _: () = {
+    impl std::fmt::Debug for ETag {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            std::fmt::Display::fmt(self, f)
+        }
+    }
+    impl std::fmt::Display for ETag {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            if self.weak {f.write_str("W/")?}
+            write!(f, "\"{}\"", self.tag)
+        }
+    }
+
+    impl From<ETag> for Value {
+        #[inline]
+        fn from(etag: ETag) -> Self {
+            Value::from(etag.to_string())
+        }
+    }
+
+    impl TypedHeader for ETag {
+        const HEADER: &'static Header = standard::ETag;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            ETag::parse(value)
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(ETag::parse(r#""xyzzy""#), Some(ETag::strong("xyzzy")));
+        assert_eq!(ETag::parse(r#"W/"xyzzy""#), Some(ETag::weak("xyzzy")));
+        assert_eq!(ETag::parse(r#""""#), Some(ETag::strong("")));
+        assert_eq!(ETag::parse(r#""a,b""#), Some(ETag::strong("a,b")));
+        for invalid in [r#"xyzzy"#, r#""xyzzy"#, r#"w/"xyzzy""#, r#""xy zzy""#, r#""a" "b""#] {
+            assert_eq!(ETag::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_etags() {
+        assert_eq!(ETags::parse(" * "), ETags::Any);
+        assert_eq!(
+            ETags::parse(r#""xyzzy", W/"r2d2xxxx" , invalid, "a,b","c3piozzzz""#),
+            ETags::List(vec![ETag::strong("xyzzy"), ETag::weak("r2d2xxxx"), ETag::strong("a,b"), ETag::strong("c3piozzzz")])
+        );
+    }
+
+    #[test]
+    fn test_comparison() {
+        /* RFC 9110 §8.8.3.2 */
+        for (a, b, strong, weak) in [
+            (r#"W/"1""#, r#"W/"1""#, false, true),
+            (r#"W/"1""#, r#"W/"2""#, false, false),
+            (r#"W/"1""#, r#""1""#,   false, true),
+            (r#""1""#,   r#""1""#,   true,  true),
+        ] {
+            let (a, b) = (ETag::parse(a).unwrap(), ETag::parse(b).unwrap());
+            assert_eq!(a.strong_eq(&b), strong);
+            assert_eq!(a.weak_eq(&b), weak);
+        }
+    }
+
+    #[test]
+    fn test_of_payload() {
+        assert_eq!(ETag::of_payload(b"Hello"), ETag::of_payload(b"Hello"));
+        assert_ne!(ETag::of_payload(b"Hello"), ETag::of_payload(b"Hellp"));
+        assert_eq!(ETag::of_payload(b"").tag(), "0-cbf29ce484222325");
+    }
+}
@@ -0,0 +1,590 @@
+//! `Forwarded` ( RFC 7239 ), de-facto `X-Forwarded-For` / `X-Forwarded-Proto` /
+//! `X-Forwarded-Host`, and resolution of the client behind trusted proxies
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, header};
+//! use whttp::util::forwarded::{TrustedProxies, ForwardingHeaders};
+//! use std::net::IpAddr;
+//!
+//! let proxies = TrustedProxies::new(["10.0.0.0/8", "fd00::/8"]);
+//!
+//! let req = Request::GET("/")
+//!     .with(header::Host, "internal:8080")
+//!     .with(header::Forwarded, r#"for=192.0.2.60;proto=https;host=example.com, for="[fd00::17]:4711""#);
+//!
+//! let client = proxies.resolve(&req, "10.1.2.3".parse().unwrap());
+//! assert_eq!(client.ip, "192.0.2.60".parse::<IpAddr>().unwrap());
+//! assert_eq!(client.proto.as_deref(), Some("https"));
+//! assert_eq!(client.host.as_deref(), Some("example.com"));
+//!
+//! /* headers from an untrusted peer are ignored */
+//! let client = proxies.resolve(&req, "203.0.113.9".parse().unwrap());
+//! assert_eq!(client.ip, "203.0.113.9".parse::<IpAddr>().unwrap());
+//! assert_eq!(client.proto, None);
+//! assert_eq!(client.host.as_deref(), Some("internal:8080"));
+//!
+//! /* behind proxies writing `X-Forwarded-*` */
+//! let proxies = TrustedProxies::new(["10.0.0.0/8"]).headers(ForwardingHeaders::XForwarded);
+//!
+//! let req = Request::GET("/")
+//!     .with(header::XForwardedFor, "192.0.2.60")
+//!     .with(header::XForwardedProto, "https");
+//! let client = proxies.resolve(&req, "10.1.2.3".parse().unwrap());
+//! assert_eq!(client.ip, "192.0.2.60".parse::<IpAddr>().unwrap());
+//! assert_eq!(client.proto.as_deref(), Some("https"));
+//! ```
+
+use super::auth::is_tchar;
+use crate::{Request, Value};
+use crate::headers::standard::{Forwarded as ForwardedHeader, Host, XForwardedFor, XForwardedHost, XForwardedProto};
+use std::borrow::Cow;
+use std::net::{IpAddr, Ipv6Addr};
+
+/// `node` of `for` and `by` ( RFC 7239 §6 )
+#[derive(Clone, PartialEq, Debug)]
+pub enum Node<'h> {
+    Ip { ip: IpAddr, port: Option<u16> },
+    Unknown,
+    /// `_` followed by an obfuscated identifier
+    Obfuscated(&'h str),
+}
+
+impl<'h> Node<'h> {
+    /// Accepts also bare IPv6 like `X-Forwarded-For` entries.
+    /// Obfuscated ports are ignored.
+    pub fn parse(node: &'h str) -> Option<Self> {
+        fn port(port: &str) -> Option<Option<u16>> {
+            match port.strip_prefix(':')? {
+                obfuscated if is_obfuscated(obfuscated) => Some(None),
+                port if port.bytes().all(|b| b.is_ascii_digit()) => port.parse().ok().map(Some),
+                _ => None
+            }
+        }
+
+        if node.eq_ignore_ascii_case("unknown") {
+            Some(Self::Unknown)
+        } else if is_obfuscated(node) {
+            Some(Self::Obfuscated(node))
+        } else if let Some(bracketed) = node.strip_prefix('[') {
+            let (ip, rest) = bracketed.split_once(']')?;
+            let ip = IpAddr::V6(ip.parse().ok()?);
+            let port = if rest.is_empty() {None} else {port(rest)?};
+            Some(Self::Ip { ip, port })
+        } else if let Ok(ipv6) = node.parse::<Ipv6Addr>() {
+            Some(Self::Ip { ip: IpAddr::V6(ipv6), port: None })
+        } else {
+            let (ip, port) = match node.find(':') {
+                Some(colon) => (&node[..colon], port(&node[colon..])?),
+                None => (node, None)
+            };
+            Some(Self::Ip { ip: IpAddr::V4(ip.parse().ok()?), port })
+        }
+    }
+
+    #[inline]
+    pub fn ip(&self) -> Option<IpAddr> {
+        match self {
+            Self::Ip { ip, .. } => Some(*ip),
+            _ => None
+        }
+    }
+}
+
+/// `"_" 1*( ALPHA / DIGIT / "." / "_" / "-" )`
+fn is_obfuscated(s: &str) -> bool {
+    s.len() > 1 && s.starts_with('_') && s.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'.' | b'_' | b'-'))
+}
+
+/// `forwarded-element` of `Forwarded`, added by each proxy
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Forwarded<'h> {
+    pub by:    Option<Node<'h>>,
+    pub for_:  Option<Node<'h>>,
+    pub host:  Option<Cow<'h, str>>,
+    pub proto: Option<Cow<'h, str>>,
+}
+
+impl<'h> Forwarded<'h> {
+    /// Parse the elements of `Forwarded` in order from the client side.
+    /// `None` if any of them is invalid, including duplicated or unknown-
+    /// valued parameters. Extension parameters are ignored.
+    pub fn parse_list(forwarded: &'h str) -> Option<Vec<Self>> {
+        let mut list = Vec::new();
+        let mut element = Self::default();
+        let mut rest = forwarded.trim_start_matches([' ', '\t']);
+        loop {
+            let (name, value, r) = parse_pair(rest)?;
+            macro_rules! set {
+                ($field:ident = $value:expr) => {
+                    match element.$field {
+                        None => element.$field = Some($value),
+                        Some(_) => return None
+                    }
+                };
+            }
+            match &*name.to_ascii_lowercase() {
+                "by"    => set!(by = Node::parse(borrowed(value)?)?),
+                "for"   => set!(for_ = Node::parse(borrowed(value)?)?),
+                "host"  => set!(host = value),
+                "proto" => set!(proto = {
+                    let valid = value.starts_with(|c: char| c.is_ascii_alphabetic())
+                        && value.bytes().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'));
+                    valid.then_some(value)?
+                }),
+                _ => ()
+            }
+
+            rest = r.trim_start_matches([' ', '\t']);
+            match rest.as_bytes().first() {
+                None => {list.push(element); break}
+                Some(b';') => rest = rest[1..].trim_start_matches([' ', '\t']),
+                Some(b',') => {
+                    list.push(std::mem::take(&mut element));
+                    rest = rest.trim_start_matches([' ', '\t', ',']);
+                    if rest.is_empty() {break}
+                }
+                _ => return None
+            }
+        }
+        Some(list)
+    }
+}
+
+/// `None` for a value having escaped characters, which are never valid in a node
+fn borrowed(value: Cow<'_, str>) -> Option<&str> {
+    match value {
+        Cow::Borrowed(value) => Some(value),
+        Cow::Owned(_) => None
+    }
+}
+
+/// `token "=" ( token / quoted-string )` at the start of `s`, with the rest
+fn parse_pair(s: &str) -> Option<(&str, Cow<'_, str>, &str)> {
+    let name_end = s.bytes().position(|b| !is_tchar(b)).unwrap_or(s.len());
+    if name_end == 0 {return None}
+    let (name, rest) = s.split_at(name_end);
+    let rest = rest.strip_prefix('=')?;
+
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let mut escaped = false;
+        for (i, b) in quoted.bytes().enumerate() {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => {
+                    let value = &quoted[..i];
+                    let value = if value.contains('\\') {
+                        let mut unescaped = String::with_capacity(value.len());
+                        let mut chars = value.chars();
+                        while let Some(c) = chars.next() {
+                            unescaped.push(if c == '\\' {chars.next()?} else {c})
+                        }
+                        Cow::Owned(unescaped)
+                    } else {
+                        Cow::Borrowed(value)
+                    };
+                    return Some((name, value, &quoted[i + 1..]))
+                }
+                _ => ()
+            }
+        }
+        None
+    } else {
+        let value_end = rest.bytes().position(|b| !is_tchar(b)).unwrap_or(rest.len());
+        if value_end == 0 {return None}
+        Some((name, Cow::Borrowed(&rest[..value_end]), &rest[value_end..]))
+    }
+}
+
+impl std::fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Ip { ip: IpAddr::V4(ip), port: None } => write!(f, "{ip}"),
+            Self::Ip { ip: IpAddr::V4(ip), port: Some(port) } => write!(f, "{ip}:{port}"),
+            Self::Ip { ip: IpAddr::V6(ip), port: None } => write!(f, "[{ip}]"),
+            Self::Ip { ip: IpAddr::V6(ip), port: Some(port) } => write!(f, "[{ip}]:{port}"),
+            Self::Unknown => f.write_str("unknown"),
+            Self::Obfuscated(obfuscated) => f.write_str(obfuscated)
+        }
+    }
+}
+
+impl std::fmt::Display for Forwarded<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn write_pair(f: &mut std::fmt::Formatter<'_>, first: &mut bool, name: &str, value: &str) -> std::fmt::Result {
+            if !std::mem::take(first) {f.write_str(";")?}
+            if !value.is_empty() && value.bytes().all(is_tchar) {
+                write!(f, "{name}={value}")
+            } else {
+                write!(f, "{name}=\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+            }
+        }
+
+        let mut first = true;
+        if let Some(by) = &self.by {write_pair(f, &mut first, "by", &by.to_string())?}
+        if let Some(for_) = &self.for_ {write_pair(f, &mut first, "for", &for_.to_string())?}
+        if let Some(host) = &self.host {write_pair(f, &mut first, "host", host)?}
+        if let Some(proto) = &self.proto {write_pair(f, &mut first, "proto", proto)?}
+        Ok(())
+    }
+}
+
+impl From<Forwarded<'_>> for Value {
+    #[inline]
+    fn from(forwarded: Forwarded<'_>) -> Self {
+        Value::from(forwarded.to_string())
+    }
+}
+
+/*=====================================================*/
+
+/// IP address range in CIDR notation like `10.0.0.0/8` or `2001:db8::/32`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Cidr {
+    addr:   IpAddr,
+    prefix: u8,
+}
+
+impl Cidr {
+    /// Panics if `prefix` exceeds the bit length of `addr`.
+    pub const fn new(addr: IpAddr, prefix: u8) -> Self {
+        let max = match addr {IpAddr::V4(_) => 32, IpAddr::V6(_) => 128};
+        if prefix > max {panic!("CIDR prefix too long")}
+        Self { addr, prefix }
+    }
+
+    /// bare address for the single address
+    pub fn parse(cidr: &str) -> Option<Self> {
+        let (addr, prefix) = match cidr.trim().split_once('/') {
+            Some((addr, prefix)) => (addr.parse().ok()?, Some(prefix.parse::<u8>().ok()?)),
+            None => (cidr.trim().parse().ok()?, None)
+        };
+        let max = match addr {IpAddr::V4(_) => 32, IpAddr::V6(_) => 128};
+        let prefix = prefix.unwrap_or(max);
+        (prefix <= max).then_some(Self { addr, prefix })
+    }
+
+    /// `ip` is in this range, where IPv4-mapped IPv6 addresses are
+    /// regarded as IPv4
+    pub fn contains(&self, ip: &IpAddr) -> bool {
+        fn masked(bits: u128, len: u8, prefix: u8) -> u128 {
+            if prefix == 0 {0} else {bits >> (len - prefix)}
+        }
+        match (self.addr, ip.to_canonical()) {
+            (IpAddr::V4(a), IpAddr::V4(b)) => masked(u32::from(a) as u128, 32, self.prefix) == masked(u32::from(b) as u128, 32, self.prefix),
+            (IpAddr::V6(a), IpAddr::V6(b)) => masked(u128::from(a), 128, self.prefix) == masked(u128::from(b), 128, self.prefix),
+            _ => false
+        }
+    }
+}
+
+/// the client as seen by the first trusted proxy
+#[derive(Clone, PartialEq, Debug)]
+pub struct Client<'req> {
+    pub ip:    IpAddr,
+    /// `None` if not forwarded
+    pub proto: Option<Cow<'req, str>>,
+    /// forwarded one, or `Host` of the request
+    pub host:  Option<Cow<'req, str>>,
+}
+
+/// headers written by the trusted proxies
+#[derive(Clone, Copy, PartialEq, Debug, Default)]
+pub enum ForwardingHeaders {
+    /// `Forwarded`
+    #[default]
+    Forwarded,
+    /// `X-Forwarded-For`, `X-Forwarded-Proto` and `X-Forwarded-Host`
+    XForwarded,
+}
+
+/// set of proxies whose forwarding headers are trusted
+#[derive(Clone, Debug, Default)]
+pub struct TrustedProxies {
+    cidrs:   Vec<Cidr>,
+    headers: ForwardingHeaders,
+}
+
+impl TrustedProxies {
+    /// Trust `Forwarded` from `cidrs`.
+    /// Panics if any of `cidrs` is invalid.
+    pub fn new(cidrs: impl IntoIterator<Item = impl AsRef<str>>) -> Self {
+        Self {
+            cidrs:   cidrs.into_iter()
+                .map(|cidr| Cidr::parse(cidr.as_ref()).unwrap_or_else(|| panic!("invalid CIDR `{}`", cidr.as_ref())))
+                .collect(),
+            headers: ForwardingHeaders::Forwarded,
+        }
+    }
+
+    /// Set which headers the trusted proxies write. The other ones are
+    /// never read, as a proxy writing one kind typically passes the other
+    /// kind sent by the client through as is.
+    #[inline]
+    pub fn headers(mut self, headers: ForwardingHeaders) -> Self {
+        self.headers = headers;
+        self
+    }
+
+    #[inline]
+    pub fn trusts(&self, ip: &IpAddr) -> bool {
+        self.cidrs.iter().any(|cidr| cidr.contains(ip))
+    }
+
+    /// Resolve the client of `req` received from `peer`.
+    ///
+    /// Starting from `peer`, hops are followed back while they are trusted
+    /// through `Forwarded` or `X-Forwarded-For`, as configured by
+    /// [`headers`](Self::headers). `proto` and `host` are taken from what
+    /// the first trusted proxy, the one facing the client, reported. The
+    /// walk stops at an invalid header or a node without IP ( `unknown` or
+    /// obfuscated ), where the last trusted proxy is regarded as the client.
+    ///
+    /// `X-Forwarded-Proto` and `-Host` are aligned to `X-Forwarded-For` from
+    /// the end, and the last known one is kept for hops beyond them, as
+    /// usually only the edge proxy sets a single value.
+    pub fn resolve<'req>(&self, req: &'req Request, peer: IpAddr) -> Client<'req> {
+        let direct = || Client {
+            ip:    peer,
+            proto: None,
+            host:  req.header(Host).map(Cow::Borrowed),
+        };
+        if !self.trusts(&peer) {
+            return direct()
+        }
+
+        match self.headers {
+            ForwardingHeaders::Forwarded => {
+                let Some(forwarded) = req.header(ForwardedHeader) else {
+                    return direct()
+                };
+                let Some(elements) = Forwarded::parse_list(forwarded) else {
+                    return direct()
+                };
+
+                let mut client = direct();
+                for element in elements.into_iter().rev() {
+                    let Some(ip) = element.for_.as_ref().and_then(Node::ip) else {
+                        break
+                    };
+                    client.ip = ip;
+                    client.proto = element.proto;
+                    if let Some(host) = element.host {
+                        client.host = Some(host);
+                    }
+                    if !self.trusts(&ip) {
+                        break
+                    }
+                }
+                client
+            }
+
+            ForwardingHeaders::XForwarded => {
+                let Some(x_forwarded_for) = req.header(XForwardedFor) else {
+                    return direct()
+                };
+
+                let nth_back = |header, n| req.header(header)
+                    .and_then(|v: &'req str| v.rsplit(',').nth(n))
+                    .map(str::trim)
+                    .filter(|v| !v.is_empty())
+                    .map(Cow::Borrowed);
+
+                let mut client = direct();
+                for (n, entry) in x_forwarded_for.rsplit(',').enumerate() {
+                    let Some(ip) = Node::parse(entry.trim()).as_ref().and_then(Node::ip) else {
+                        break
+                    };
+                    client.ip = ip;
+                    if let Some(proto) = nth_back(XForwardedProto, n) {
+                        client.proto = Some(proto);
+                    }
+                    if let Some(host) = nth_back(XForwardedHost, n) {
+                        client.host = Some(host);
+                    }
+                    if !self.trusts(&ip) {
+                        break
+                    }
+                }
+                client
+            }
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header;
+
+    fn ip(ip: &str) -> IpAddr {
+        ip.parse().unwrap()
+    }
+
+    #[test]
+    fn test_node() {
+        assert_eq!(Node::parse("192.0.2.43"), Some(Node::Ip { ip: ip("192.0.2.43"), port: None }));
+        assert_eq!(Node::parse("192.0.2.43:47011"), Some(Node::Ip { ip: ip("192.0.2.43"), port: Some(47011) }));
+        assert_eq!(Node::parse("[2001:db8:cafe::17]"), Some(Node::Ip { ip: ip("2001:db8:cafe::17"), port: None }));
+        assert_eq!(Node::parse("[2001:db8:cafe::17]:4711"), Some(Node::Ip { ip: ip("2001:db8:cafe::17"), port: Some(4711) }));
+        assert_eq!(Node::parse("2001:db8:cafe::17"), Some(Node::Ip { ip: ip("2001:db8:cafe::17"), port: None }));
+        assert_eq!(Node::parse("10.0.0.1:_abc"), Some(Node::Ip { ip: ip("10.0.0.1"), port: None }));
+        assert_eq!(Node::parse("unknown"), Some(Node::Unknown));
+        assert_eq!(Node::parse("_hidden"), Some(Node::Obfuscated("_hidden")));
+        for invalid in ["", "_", "example.com", "192.0.2.43:", "192.0.2.43:99999", "[2001:db8::17", "[192.0.2.43]", "_a b"] {
+            assert_eq!(Node::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_parse_list() {
+        /* RFC 7239 §4, §7.1 */
+        assert_eq!(Forwarded::parse_list(r#"for="_gazonk""#), Some(vec![
+            Forwarded { for_: Some(Node::Obfuscated("_gazonk")), ..Default::default() }
+        ]));
+        assert_eq!(Forwarded::parse_list(r#"For="[2001:db8:cafe::17]:4711""#), Some(vec![
+            Forwarded { for_: Some(Node::Ip { ip: ip("2001:db8:cafe::17"), port: Some(4711) }), ..Default::default() }
+        ]));
+        assert_eq!(Forwarded::parse_list("for=192.0.2.60;proto=http;by=203.0.113.43"), Some(vec![
+            Forwarded {
+                for_:  Some(Node::Ip { ip: ip("192.0.2.60"), port: None }),
+                proto: Some("http".into()),
+                by:    Some(Node::Ip { ip: ip("203.0.113.43"), port: None }),
+                host:  None,
+            }
+        ]));
+        assert_eq!(Forwarded::parse_list("for=192.0.2.43, for=198.51.100.17"), Some(vec![
+            Forwarded { for_: Some(Node::Ip { ip: ip("192.0.2.43"), port: None }), ..Default::default() },
+            Forwarded { for_: Some(Node::Ip { ip: ip("198.51.100.17"), port: None }), ..Default::default() },
+        ]));
+        assert_eq!(Forwarded::parse_list(r#"for=unknown;host="example.com:8080";ext=1"#), Some(vec![
+            Forwarded { for_: Some(Node::Unknown), host: Some("example.com:8080".into()), ..Default::default() }
+        ]));
+
+        for invalid in [
+            "", "for", "for=", "for=192.0.2.43;", "for=192.0.2.43 proto=http", "for=[2001:db8::1]",
+            "for=192.0.2.43;for=192.0.2.44", "for=example.com", r#"proto="h t t p""#, r#"for="192.0.2.43"#,
+        ] {
+            assert_eq!(Forwarded::parse_list(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let forwarded = Forwarded {
+            for_:  Some(Node::Ip { ip: ip("2001:db8:cafe::17"), port: Some(4711) }),
+            proto: Some("https".into()),
+            by:    Some(Node::Obfuscated("_proxy1")),
+            host:  Some("example.com".into()),
+        };
+        assert_eq!(forwarded.to_string(), r#"by=_proxy1;for="[2001:db8:cafe::17]:4711";host=example.com;proto=https"#);
+        assert_eq!(Forwarded::parse_list(&forwarded.to_string()), Some(vec![forwarded]));
+    }
+
+    #[test]
+    fn test_cidr() {
+        let cidr = Cidr::parse("10.0.0.0/8").unwrap();
+        assert!(cidr.contains(&ip("10.255.0.1")));
+        assert!(cidr.contains(&ip("::ffff:10.0.0.1")));
+        assert!(!cidr.contains(&ip("11.0.0.1")));
+        assert!(!cidr.contains(&ip("::1")));
+
+        let cidr = Cidr::parse("2001:db8::/32").unwrap();
+        assert!(cidr.contains(&ip("2001:db8:ffff::1")));
+        assert!(!cidr.contains(&ip("2001:db9::1")));
+
+        assert!(Cidr::parse("0.0.0.0/0").unwrap().contains(&ip("192.0.2.1")));
+        assert!(Cidr::parse("192.0.2.1").unwrap().contains(&ip("192.0.2.1")));
+        assert!(!Cidr::parse("192.0.2.1").unwrap().contains(&ip("192.0.2.2")));
+        for invalid in ["10.0.0.0/33", "::/129", "10.0.0.0/", "example.com/8"] {
+            assert_eq!(Cidr::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_resolve_forwarded() {
+        let proxies = TrustedProxies::new(["10.0.0.0/8"]);
+        let req = |forwarded: &'static str| Request::GET("/")
+            .with(header::Host, "internal")
+            .with(header::Forwarded, forwarded);
+
+        /* spoofed leftmost element is not reached */
+        let req1 = req("for=1.1.1.1, for=192.0.2.60;proto=https;host=example.com, for=10.0.0.2");
+        assert_eq!(proxies.resolve(&req1, ip("10.0.0.1")), Client {
+            ip:    ip("192.0.2.60"),
+            proto: Some("https".into()),
+            host:  Some("example.com".into()),
+        });
+
+        /* all trusted: the leftmost one */
+        let req2 = req("for=10.0.0.3;proto=http, for=10.0.0.2");
+        assert_eq!(proxies.resolve(&req2, ip("10.0.0.1")).ip, ip("10.0.0.3"));
+
+        /* unknown node: the last trusted proxy */
+        let req3 = req("for=unknown, for=10.0.0.2");
+        assert_eq!(proxies.resolve(&req3, ip("10.0.0.1")).ip, ip("10.0.0.2"));
+
+        /* invalid header: the peer */
+        let req4 = req("for=192.0.2.60;;");
+        assert_eq!(proxies.resolve(&req4, ip("10.0.0.1")), Client {
+            ip:    ip("10.0.0.1"),
+            proto: None,
+            host:  Some("internal".into()),
+        });
+
+        /* untrusted peer */
+        assert_eq!(proxies.resolve(&req1, ip("192.0.2.1")).ip, ip("192.0.2.1"));
+
+        /* X-Forwarded-* is not read */
+        let req5 = Request::GET("/").with(header::XForwardedFor, "192.0.2.60");
+        assert_eq!(proxies.resolve(&req5, ip("10.0.0.1")).ip, ip("10.0.0.1"));
+    }
+
+    #[test]
+    fn test_resolve_x_forwarded() {
+        let proxies = TrustedProxies::new(["10.0.0.0/8", "fd00::/8"]).headers(ForwardingHeaders::XForwarded);
+
+        let req = Request::GET("/")
+            .with(header::XForwardedFor, "1.1.1.1, 192.0.2.60, fd00::2")
+            .with(header::XForwardedProto, "https, http")
+            .with(header::XForwardedHost, "example.com");
+        assert_eq!(proxies.resolve(&req, ip("10.0.0.1")), Client {
+            ip:    ip("192.0.2.60"),
+            proto: Some("https".into()),
+            host:  Some("example.com".into()),
+        });
+
+        let req = Request::GET("/")
+            .with(header::XForwardedFor, "192.0.2.60")
+            .with(header::XForwardedProto, "https");
+        assert_eq!(proxies.resolve(&req, ip("::ffff:10.0.0.1")), Client {
+            ip:    ip("192.0.2.60"),
+            proto: Some("https".into()),
+            host:  None,
+        });
+
+        /* single proto and host set by the edge proxy over two hops */
+        let req = Request::GET("/")
+            .with(header::XForwardedFor, "192.0.2.60, 10.0.0.2")
+            .with(header::XForwardedProto, "https")
+            .with(header::XForwardedHost, "example.com");
+        assert_eq!(proxies.resolve(&req, ip("10.0.0.1")), Client {
+            ip:    ip("192.0.2.60"),
+            proto: Some("https".into()),
+            host:  Some("example.com".into()),
+        });
+
+        /* client-sent Forwarded passed through by the proxies is never read */
+        let req = Request::GET("/")
+            .with(header::Forwarded, "for=1.2.3.4")
+            .with(header::XForwardedFor, "192.0.2.60");
+        assert_eq!(proxies.resolve(&req, ip("10.0.0.1")).ip, ip("192.0.2.60"));
+        let req = Request::GET("/")
+            .with(header::Forwarded, "for=1.2.3.4");
+        assert_eq!(proxies.resolve(&req, ip("10.0.0.1")).ip, ip("10.0.0.1"));
+    }
+}
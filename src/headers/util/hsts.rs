@@ -0,0 +1,122 @@
+use crate::headers::{Header, Value, TypedHeader, standard};
+
+/// `Strict-Transport-Security` ( RFC 6797 §6.1 )
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Response, header};
+/// use whttp::util::Hsts;
+///
+/// const HSTS: Hsts = Hsts::new(63072000).include_subdomains().preload();
+///
+/// let res = Response::OK().with(header::StrictTransportSecurity, HSTS);
+/// assert_eq!(res.header(header::StrictTransportSecurity), Some("max-age=63072000; includeSubDomains; preload"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Hsts {
+    pub max_age:            u64,
+    pub include_subdomains: bool,
+    /// non-standard `preload` for the HSTS preload list of browsers
+    pub preload:            bool,
+}
+
+impl Hsts {
+    #[inline]
+    pub const fn new(max_age: u64) -> Self {
+        Self { max_age, include_subdomains: false, preload: false }
+    }
+
+    #[inline]
+    pub const fn include_subdomains(mut self) -> Self {
+        self.include_subdomains = true;
+        self
+    }
+
+    #[inline]
+    pub const fn preload(mut self) -> Self {
+        self.preload = true;
+        self
+    }
+
+    /// `None` without valid `max-age` or with duplicated directives.
+    /// Unknown directives are ignored.
+    pub fn parse(hsts: &str) -> Option<Self> {
+        let (mut max_age, mut include_subdomains, mut preload) = (None, false, false);
+        for directive in hsts.split(';') {
+            let directive = directive.trim();
+            if directive.is_empty() {continue}
+
+            let (name, value) = match directive.split_once('=') {
+                Some((name, value)) => {
+                    let value = value.trim();
+                    (name.trim_end(), Some(value.strip_prefix('"').and_then(|v| v.strip_suffix('"')).unwrap_or(value)))
+                }
+                None => (directive, None)
+            };
+            let duplicated = if name.eq_ignore_ascii_case("max-age") {
+                let value = value.filter(|v| !v.is_empty() && v.bytes().all(|b| b.is_ascii_digit()))?;
+                max_age.replace(value.parse().unwrap_or(u64::MAX)).is_some()
+            } else if name.eq_ignore_ascii_case("includeSubDomains") {
+                std::mem::replace(&mut include_subdomains, true)
+            } else if name.eq_ignore_ascii_case("preload") {
+                std::mem::replace(&mut preload, true)
+            } else {
+                false
+            };
+            if duplicated {return None}
+        }
+        Some(Self { max_age: max_age?, include_subdomains, preload })
+    }
+}
+
+const _: () = {
+    impl std::fmt::Display for Hsts {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "max-age={}", self.max_age)?;
+            if self.include_subdomains {f.write_str("; includeSubDomains")?}
+            if self.preload {f.write_str("; preload")?}
+            Ok(())
+        }
+    }
+
+    impl From<Hsts> for Value {
+        #[inline]
+        fn from(hsts: Hsts) -> Self {
+            Value::from(hsts.to_string())
+        }
+    }
+
+    impl TypedHeader for Hsts {
+        const HEADER: &'static Header = standard::StrictTransportSecurity;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            Hsts::parse(value)
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        /* RFC 6797 §6.2 */
+        assert_eq!(Hsts::parse("max-age=31536000"), Some(Hsts::new(31536000)));
+        assert_eq!(Hsts::parse("max-age=15768000 ; includeSubDomains"), Some(Hsts::new(15768000).include_subdomains()));
+        assert_eq!(Hsts::parse(r#"max-age="0"; INCLUDESUBDOMAINS; preload; unknown=1"#), Some(Hsts::new(0).include_subdomains().preload()));
+        for invalid in ["", "includeSubDomains", "max-age", "max-age=-1", "max-age=1; max-age=2", "max-age=1; preload; preload"] {
+            assert_eq!(Hsts::parse(invalid), None, "{invalid}");
+        }
+        assert_eq!(Hsts::new(1).preload().to_string(), "max-age=1; preload");
+    }
+}
@@ -0,0 +1,266 @@
+use crate::headers::Value;
+use super::auth::is_tchar;
+use super::content_disposition::{decode_ext_value, NON_ATTR_CHAR};
+use percent_encoding::utf8_percent_encode;
+use std::borrow::Cow;
+
+/// a `link-value` of `Link` ( RFC 8288 §3 )
+///
+/// Multiple links are sent by appending each to the header, and parsed
+/// by [`parse_list`](Link::parse_list).
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Response, header};
+/// use whttp::util::Link;
+///
+/// let mut res = Response::OK();
+/// res.append(header::Link, Link::new("/items?page=3").with_rel("next"));
+/// res.append(header::Link, Link::new("/style.css").with_rel("preload").with_param("as", "style"));
+/// assert_eq!(
+///     res.header(header::Link),
+///     Some(r#"</items?page=3>; rel="next",</style.css>; rel="preload"; as="style""#)
+/// );
+///
+/// let links = Link::parse_list(res.header(header::Link).unwrap()).unwrap();
+/// let next = links.iter().find(|link| link.has_rel("next")).unwrap();
+/// assert_eq!(next.target, "/items?page=3");
+/// ```
+#[derive(Clone, PartialEq, Debug)]
+pub struct Link {
+    /// URI-Reference, not resolved against the context
+    pub target: String,
+    /// parameters in order, with lowercase names and unquoted values
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl Link {
+    /// Panics if `target` contains `>` or characters not allowed in header values.
+    pub fn new(target: impl Into<String>) -> Self {
+        let target = target.into();
+        if !target.bytes().all(|b| matches!(b, b'!'..=b'~') && b != b'>') {panic!("invalid link target")}
+        Self { target, params: Vec::new() }
+    }
+
+    /// Panics if `name` is not a token. The value of the same `name` is
+    /// overwritten, so use `push` on `params` for ones allowed to repeat
+    /// like `hreflang`.
+    pub fn with_param(mut self, name: &str, value: impl Into<String>) -> Self {
+        if name.is_empty() || !name.bytes().all(is_tchar) {panic!("invalid link-param name")}
+        let (name, value) = (name.to_ascii_lowercase(), Some(value.into()));
+        match self.params.iter_mut().find(|(n, _)| *n == name) {
+            Some(param) => param.1 = value,
+            None => self.params.push((name, value))
+        }
+        self
+    }
+
+    /// space-separated relation types like `next` or `preload`
+    #[inline]
+    pub fn with_rel(self, rel: impl Into<String>) -> Self {
+        self.with_param("rel", rel)
+    }
+
+    /// media type hint of the target
+    #[inline]
+    pub fn with_type(self, media_type: impl Into<Value>) -> Self {
+        self.with_param("type", &*media_type.into())
+    }
+
+    /// Non-ASCII `title` is sent by `title*` together with an ASCII
+    /// fallback in `title`.
+    pub fn with_title(self, title: impl Into<String>) -> Self {
+        let title = title.into();
+        if title.bytes().all(|b| matches!(b, b' '..=b'~')) {
+            self.with_param("title", title)
+        } else {
+            let fallback = title.chars().filter(|c| !c.is_ascii_control()).map(|c| if c.is_ascii() {c} else {'_'}).collect::<String>();
+            let encoded = format!("UTF-8''{}", utf8_percent_encode(&title, NON_ATTR_CHAR));
+            self.with_param("title", fallback).with_param("title*", encoded)
+        }
+    }
+
+    /// Parse comma-separated link-values. `None` if any of them is invalid.
+    pub fn parse_list(links: &str) -> Option<Vec<Self>> {
+        let mut list = Vec::new();
+        let mut rest = links;
+        loop {
+            rest = rest.trim_start_matches([' ', '\t', ',']);
+            if rest.is_empty() {break}
+
+            let (target, r) = rest.strip_prefix('<')?.split_once('>')?;
+            let mut link = Self { target: target.trim().into(), params: Vec::new() };
+            rest = r.trim_start_matches([' ', '\t']);
+            while let Some(r) = rest.strip_prefix(';') {
+                let (name, value, r) = parse_param(r.trim_start_matches([' ', '\t']))?;
+                link.params.push((name.to_ascii_lowercase(), value));
+                rest = r.trim_start_matches([' ', '\t']);
+            }
+            if !(rest.is_empty() || rest.starts_with(',')) {return None}
+
+            list.push(link);
+        }
+        Some(list)
+    }
+
+    /// value of the first param of `name` ( case-insensitive ),
+    /// `Some("")` for one without a value
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.iter()
+            .find_map(|(n, v)| n.eq_ignore_ascii_case(name).then(|| v.as_deref().unwrap_or_default()))
+    }
+
+    /// relation types in `rel`
+    pub fn rels(&self) -> impl Iterator<Item = &str> {
+        self.param("rel").unwrap_or_default().split_ascii_whitespace()
+    }
+
+    /// `rel` includes `rel` ( case-insensitive )
+    #[inline]
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rels().any(|r| r.eq_ignore_ascii_case(rel))
+    }
+
+    #[inline]
+    pub fn type_(&self) -> Option<&str> {
+        self.param("type")
+    }
+
+    /// `title*` if it's valid, or else `title`
+    pub fn title(&self) -> Option<Cow<'_, str>> {
+        match self.param("title*").and_then(decode_ext_value) {
+            Some(title) => Some(Cow::Owned(title)),
+            None => self.param("title").map(Cow::Borrowed)
+        }
+    }
+}
+
+/// `token [ BWS "=" BWS ( token / quoted-string ) ]` at the start of `s`, with the rest
+fn parse_param(s: &str) -> Option<(&str, Option<String>, &str)> {
+    let name_end = s.bytes().position(|b| !is_tchar(b)).unwrap_or(s.len());
+    if name_end == 0 {return None}
+    let (name, rest) = s.split_at(name_end);
+    let Some(rest) = rest.trim_start_matches([' ', '\t']).strip_prefix('=') else {
+        return Some((name, None, rest))
+    };
+    let rest = rest.trim_start_matches([' ', '\t']);
+
+    if let Some(quoted) = rest.strip_prefix('"') {
+        let mut value = String::new();
+        let mut escaped = false;
+        for (i, c) in quoted.char_indices() {
+            match c {
+                _ if escaped => {value.push(c); escaped = false}
+                '\\' => escaped = true,
+                '"' => return Some((name, Some(value), &quoted[i + 1..])),
+                _ => value.push(c)
+            }
+        }
+        None
+    } else {
+        let value_end = rest.bytes().position(|b| !is_tchar(b)).unwrap_or(rest.len());
+        if value_end == 0 {return None}
+        let (value, rest) = rest.split_at(value_end);
+        Some((name, Some(value.into()), rest))
+    }
+}
+
+const _: () = {
+    impl std::fmt::Display for Link {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "<{}>", self.target)?;
+            for (name, value) in &self.params {
+                match value {
+                    None => write!(f, "; {name}")?,
+                    /* ext-value must not be quoted ( RFC 8288 §3 ) */
+                    Some(value) if name.ends_with('*') => write!(f, "; {name}={value}")?,
+                    Some(value) => {
+                        write!(f, "; {name}=\"")?;
+                        for c in value.chars() {
+                            match c {
+                                '"' | '\\'        => write!(f, "\\{c}")?,
+                                ' '..='~'         => write!(f, "{c}")?,
+                                _ if c.is_ascii() => (),
+                                _                 => f.write_str("_")?
+                            }
+                        }
+                        f.write_str("\"")?
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl From<Link> for Value {
+        #[inline]
+        fn from(link: Link) -> Self {
+            Value::from(link.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_list() {
+        /* RFC 8288 §3.5 */
+        assert_eq!(
+            Link::parse_list(r#"<http://example.com/TheBook/chapter2>; rel="previous"; title="previous chapter""#),
+            Some(vec![Link::new("http://example.com/TheBook/chapter2").with_rel("previous").with_title("previous chapter")])
+        );
+        assert_eq!(
+            Link::parse_list(r#"</>; rel="http://example.net/foo""#),
+            Some(vec![Link::new("/").with_rel("http://example.net/foo")])
+        );
+        assert_eq!(
+            Link::parse_list(r##"</terms>; rel="copyright"; anchor="#foo""##),
+            Some(vec![Link::new("/terms").with_rel("copyright").with_param("anchor", "#foo")])
+        );
+
+        let links = Link::parse_list(concat!(
+            r#"</TheBook/chapter2>; rel="previous"; title*=UTF-8'de'letztes%20Kapitel, "#,
+            r#"</TheBook/chapter4>; rel="next"; title*=UTF-8'de'n%c3%a4chstes%20Kapitel"#,
+        )).unwrap();
+        assert_eq!(links.len(), 2);
+        assert_eq!(links[0].title().as_deref(), Some("letztes Kapitel"));
+        assert_eq!(links[1].title().as_deref(), Some("nächstes Kapitel"));
+        assert!(links[1].has_rel("next"));
+
+        let links = Link::parse_list(r#"<http://example.org/>; rel="start http://example.net/relation/other""#).unwrap();
+        assert_eq!(links[0].rels().collect::<Vec<_>>(), ["start", "http://example.net/relation/other"]);
+
+        /* URI with `,` and `;`, valueless and case-insensitive params */
+        let links = Link::parse_list("<https://example.com/a,b;c> ; REL = preload ; crossorigin,</x>").unwrap();
+        assert_eq!(links[0].target, "https://example.com/a,b;c");
+        assert_eq!(links[0].param("rel"), Some("preload"));
+        assert_eq!(links[0].param("crossorigin"), Some(""));
+        assert_eq!(links[1], Link::new("/x"));
+
+        for invalid in ["/x", "</x", "</x> rel=next", "</x>; =next", "</x>; rel=\"next", "</x>; rel=a b"] {
+            assert_eq!(Link::parse_list(invalid), None, "{invalid}");
+        }
+        assert_eq!(Link::parse_list(""), Some(vec![]));
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            Link::new("/items?page=2").with_rel("prev").with_type("application/json").to_string(),
+            r#"</items?page=2>; rel="prev"; type="application/json""#
+        );
+        assert_eq!(
+            Link::new("/ch4").with_rel("next").with_title("nächstes \"Kapitel\"").to_string(),
+            r#"</ch4>; rel="next"; title="n_chstes \"Kapitel\""; title*=UTF-8''n%C3%A4chstes%20%22Kapitel%22"#
+        );
+        assert_eq!(Link::new("/").with_rel("a").with_rel("b").to_string(), r#"</>; rel="b""#);
+
+        let link = Link::new("/ch4").with_rel("next").with_title("nächstes Kapitel").with_param("hreflang", "de");
+        assert_eq!(Link::parse_list(&link.to_string()), Some(vec![link]));
+    }
+}
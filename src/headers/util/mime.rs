@@ -0,0 +1,378 @@
+//! Media types for `Content-Type` and `Accept`
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response};
+//! use whttp::util::mime::{self, Mime};
+//!
+//! fn handle(req: &Request) -> Response {
+//!     let Some(ct) = req.headers().typed_get::<Mime>() else {
+//!         return Response::BadRequest()
+//!     };
+//!     if !ct.matches(&mime::TEXT_STAR) {
+//!         return Response::UnsupportedMediaType()
+//!     }
+//!     if ct.charset().is_some_and(|charset| !charset.eq_ignore_ascii_case("UTF-8")) {
+//!         return Response::UnsupportedMediaType()
+//!     }
+//!
+//!     Response::OK()
+//!         .with_payload(mime::APPLICATION_JSON, br#"{"ok":true}"#.as_slice())
+//! }
+//! ```
+
+use crate::headers::{Header, Value, TypedHeader, standard};
+use std::borrow::Cow;
+
+/// `type/subtype[+suffix] *( ; name=value )`, where `value` is
+/// a token or a quoted-string.
+///
+/// Type, subtype, suffix and parameter names are compared case-insensitively.
+#[derive(Clone)]
+pub struct Mime {
+    source: Cow<'static, str>,
+    /// index of `/`
+    slash:  u16,
+    /// index of `+` of suffix, or `end` if no suffix
+    plus:   u16,
+    /// end of `type/subtype+suffix`
+    end:    u16,
+}
+
+macro_rules! constants {
+    ($( $name:ident = $mime:literal ),* $(,)?) => {$(
+        #[doc = concat!("`", $mime, "`")]
+        pub const $name: Mime = Mime::new($mime);
+    )*};
+}
+constants! {
+    STAR_STAR                       = "*/*",
+    TEXT_STAR                       = "text/*",
+    IMAGE_STAR                      = "image/*",
+    AUDIO_STAR                      = "audio/*",
+    VIDEO_STAR                      = "video/*",
+
+    TEXT_PLAIN                      = "text/plain",
+    TEXT_PLAIN_UTF_8                = "text/plain; charset=UTF-8",
+    TEXT_HTML                       = "text/html",
+    TEXT_HTML_UTF_8                 = "text/html; charset=UTF-8",
+    TEXT_CSS                        = "text/css",
+    TEXT_CSV                        = "text/csv",
+    TEXT_JAVASCRIPT                 = "text/javascript",
+    TEXT_EVENT_STREAM               = "text/event-stream",
+
+    APPLICATION_JSON                = "application/json",
+    APPLICATION_XML                 = "application/xml",
+    APPLICATION_PDF                 = "application/pdf",
+    APPLICATION_WASM                = "application/wasm",
+    APPLICATION_OCTET_STREAM        = "application/octet-stream",
+    APPLICATION_WWW_FORM_URLENCODED = "application/x-www-form-urlencoded",
+
+    MULTIPART_FORM_DATA             = "multipart/form-data",
+    MULTIPART_BYTERANGES            = "multipart/byteranges",
+
+    IMAGE_PNG                       = "image/png",
+    IMAGE_JPEG                      = "image/jpeg",
+    IMAGE_GIF                       = "image/gif",
+    IMAGE_WEBP                      = "image/webp",
+    IMAGE_SVG                       = "image/svg+xml",
+}
+
+#[inline(always)]
+const fn is_tchar(b: u8) -> bool {
+    matches!(b,
+        | b'!' | b'#' | b'$' | b'%' | b'&' | b'\'' | b'*' | b'+' | b'-' | b'.' | b'^' | b'_' | b'`' | b'|' | b'~'
+        | b'0'..=b'9' | b'a'..=b'z' | b'A'..=b'Z'
+    )
+}
+
+#[inline(always)]
+const fn is_ows(b: u8) -> bool {
+    matches!(b, b' ' | b'\t')
+}
+
+#[inline(always)]
+const fn is_qdtext(b: u8) -> bool {
+    matches!(b, b'\t' | b' ' | 0x21 | 0x23..=0x5B | 0x5D..=0x7E)
+}
+
+/// validate the whole media type and return `(slash, plus, end)`
+const fn scan(bytes: &[u8]) -> Option<(u16, u16, u16)> {
+    let len = bytes.len();
+    if len > u16::MAX as usize {return None}
+
+    let mut i = 0;
+    while i < len && is_tchar(bytes[i]) {i += 1}
+    if i == 0 || i == len || bytes[i] != b'/' {return None}
+    let slash = i;
+
+    i += 1;
+    let subtype = i;
+    let mut plus = None;
+    while i < len && is_tchar(bytes[i]) {
+        if bytes[i] == b'+' && i > subtype {plus = Some(i)}
+        i += 1
+    }
+    if i == subtype {return None}
+    let end = i;
+    let plus = match plus {Some(p) => p, None => end};
+
+    loop {
+        while i < len && is_ows(bytes[i]) {i += 1}
+        if i == len {break}
+        if bytes[i] != b';' {return None}
+        i += 1;
+        while i < len && is_ows(bytes[i]) {i += 1}
+        if i == len {break}
+        if bytes[i] == b';' {continue}
+
+        let name = i;
+        while i < len && is_tchar(bytes[i]) {i += 1}
+        if i == name || i == len || bytes[i] != b'=' {return None}
+        i += 1;
+
+        if i < len && bytes[i] == b'"' {
+            i += 1;
+            loop {
+                if i >= len {return None}
+                match bytes[i] {
+                    b'"' => {i += 1; break}
+                    b'\\' => {
+                        if i + 1 >= len || !(is_qdtext(bytes[i + 1]) || bytes[i + 1] == b'"' || bytes[i + 1] == b'\\') {
+                            return None
+                        }
+                        i += 2
+                    }
+                    b if is_qdtext(b) => i += 1,
+                    _ => return None
+                }
+            }
+        } else {
+            let value = i;
+            while i < len && is_tchar(bytes[i]) {i += 1}
+            if i == value {return None}
+        }
+    }
+
+    Some((slash as u16, plus as u16, end as u16))
+}
+
+impl Mime {
+    /// Panics if `mime` is invalid.
+    pub const fn new(mime: &'static str) -> Self {
+        let Some((slash, plus, end)) = scan(mime.as_bytes()) else {
+            panic!("invalid media type")
+        };
+        Self { source: Cow::Borrowed(mime), slash, plus, end }
+    }
+
+    pub fn parse(mime: impl Into<Cow<'static, str>>) -> Option<Self> {
+        let source = mime.into();
+        let (slash, plus, end) = scan(source.as_bytes())?;
+        Some(Self { source, slash, plus, end })
+    }
+
+    #[inline]
+    pub fn as_str(&self) -> &str {
+        &self.source
+    }
+
+    /// `type/subtype+suffix` without parameters
+    #[inline]
+    pub fn essence(&self) -> &str {
+        &self.source[..self.end as usize]
+    }
+
+    #[inline]
+    pub fn type_(&self) -> &str {
+        &self.source[..self.slash as usize]
+    }
+
+    /// including suffix like `svg+xml`
+    #[inline]
+    pub fn subtype(&self) -> &str {
+        &self.source[self.slash as usize + 1..self.end as usize]
+    }
+
+    /// `json` of `application/vnd.api+json`
+    #[inline]
+    pub fn suffix(&self) -> Option<&str> {
+        (self.plus < self.end).then(|| &self.source[self.plus as usize + 1..self.end as usize])
+    }
+
+    /// iterate parameters in order, unquoting quoted-string values
+    pub fn params(&self) -> impl Iterator<Item = (&str, Cow<'_, str>)> {
+        Params { rest: &self.source[self.end as usize..] }
+    }
+
+    /// value of the parameter of `name` ( case-insensitive )
+    pub fn param(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.params().find_map(|(n, v)| n.eq_ignore_ascii_case(name).then_some(v))
+    }
+
+    #[inline]
+    pub fn charset(&self) -> Option<Cow<'_, str>> {
+        self.param("charset")
+    }
+
+    #[inline]
+    pub fn boundary(&self) -> Option<Cow<'_, str>> {
+        self.param("boundary")
+    }
+
+    /// Whether `self` is in the media `range`:
+    ///
+    /// - `*` of `range` matches any type or subtype
+    /// - every parameter of `range` must be in `self` with the same value
+    ///   ( `charset` is compared case-insensitively )
+    ///
+    /// ```
+    /// use whttp::util::mime::{self, Mime};
+    ///
+    /// let ct = Mime::parse("Text/HTML; Charset=\"utf-8\"").unwrap();
+    /// assert!(ct.matches(&mime::TEXT_HTML));
+    /// assert!(ct.matches(&mime::TEXT_HTML_UTF_8));
+    /// assert!(ct.matches(&mime::TEXT_STAR));
+    /// assert!(ct.matches(&mime::STAR_STAR));
+    /// assert!(!ct.matches(&mime::TEXT_PLAIN));
+    /// assert!(!mime::TEXT_HTML.matches(&mime::TEXT_HTML_UTF_8));
+    /// ```
+    pub fn matches(&self, range: &Mime) -> bool {
+        (range.type_() == "*" || range.type_().eq_ignore_ascii_case(self.type_())) &&
+        (range.subtype() == "*" || range.subtype().eq_ignore_ascii_case(self.subtype())) &&
+        range.params().all(|(name, value)| self.param(name).is_some_and(|v| eq_param(name, &v, &value)))
+    }
+}
+
+fn eq_param(name: &str, a: &str, b: &str) -> bool {
+    if name.eq_ignore_ascii_case("charset") {
+        a.eq_ignore_ascii_case(b)
+    } else {
+        a == b
+    }
+}
+
+struct Params<'m> {
+    rest: &'m str,
+}
+impl<'m> Iterator for Params<'m> {
+    type Item = (&'m str, Cow<'m, str>);
+
+    /// `rest` has been validated by `scan`
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self.rest.trim_start_matches([' ', '\t', ';']);
+        let (name, rest) = rest.split_once('=')?;
+
+        let (value, rest) = if let Some(quoted) = rest.strip_prefix('"') {
+            let bytes = quoted.as_bytes();
+            let (mut i, mut escaped) = (0, false);
+            while bytes[i] != b'"' {
+                if bytes[i] == b'\\' {escaped = true; i += 1}
+                i += 1
+            }
+            let value = if escaped {
+                let mut unquoted = String::with_capacity(i);
+                let mut chars = quoted[..i].chars();
+                while let Some(c) = chars.next() {
+                    unquoted.push(if c == '\\' {chars.next().unwrap()} else {c})
+                }
+                Cow::Owned(unquoted)
+            } else {
+                Cow::Borrowed(&quoted[..i])
+            };
+            (value, &quoted[i + 1..])
+        } else {
+            let end = rest.find(|c: char| !c.is_ascii() || !is_tchar(c as u8)).unwrap_or(rest.len());
+            (Cow::Borrowed(&rest[..end]), &rest[end..])
+        };
+
+        self.rest = rest;
+        Some((name, value))
+    }
+}
+
+const _: () = {
+    /// type, subtype and parameters are the same ( in any order )
+    impl PartialEq for Mime {
+        fn eq(&self, other: &Self) -> bool {
+            self.essence().eq_ignore_ascii_case(other.essence()) &&
+            self.params().count() == other.params().count() &&
+            self.matches(other)
+        }
+    }
+
+    impl std::fmt::Debug for Mime {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            std::fmt::Debug::fmt(self.as_str(), f)
+        }
+    }
+    impl std::fmt::Display for Mime {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
+
+    impl From<Mime> for Value {
+        #[inline]
+        fn from(mime: Mime) -> Self {
+            Value::from(mime.source)
+        }
+    }
+
+    impl TypedHeader for Mime {
+        const HEADER: &'static Header = standard::ContentType;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            Mime::parse(value.trim().to_owned())
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.source.clone())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let m = Mime::parse("application/vnd.api+json; charset=utf-8; boundary=\"a \\\"b\\\" c\"").unwrap();
+        assert_eq!(m.type_(), "application");
+        assert_eq!(m.subtype(), "vnd.api+json");
+        assert_eq!(m.suffix(), Some("json"));
+        assert_eq!(m.essence(), "application/vnd.api+json");
+        assert_eq!(m.charset().as_deref(), Some("utf-8"));
+        assert_eq!(m.boundary().as_deref(), Some("a \"b\" c"));
+        assert_eq!(m.param("CHARSET").as_deref(), Some("utf-8"));
+
+        let m = Mime::parse("multipart/form-data;boundary=----xyz ;").unwrap();
+        assert_eq!(m.suffix(), None);
+        assert_eq!(m.params().collect::<Vec<_>>(), [("boundary", Cow::Borrowed("----xyz"))]);
+
+        for invalid in [
+            "", "text", "text/", "/plain", "text /plain",
+            "text/plain; charset", "text/plain; charset=", "text/plain; charset=\"utf-8",
+            "text/plain charset=utf-8", "text/plain; a=b c",
+        ] {
+            assert_eq!(Mime::parse(invalid), None, "{invalid:?}");
+        }
+        assert_eq!(Mime::parse("text/plain; ;"), Some(TEXT_PLAIN));
+    }
+
+    #[test]
+    fn test_eq_and_match() {
+        assert_eq!(Mime::parse("TEXT/Plain; CHARSET=utf-8").unwrap(), TEXT_PLAIN_UTF_8);
+        assert_ne!(TEXT_PLAIN, TEXT_PLAIN_UTF_8);
+        assert!(IMAGE_SVG.matches(&IMAGE_STAR));
+        assert!(!IMAGE_SVG.matches(&TEXT_STAR));
+        assert!(!TEXT_PLAIN.matches(&Mime::new("*/html")));
+        assert_eq!(Mime::decode(&Value::new(" text/plain; charset=UTF-8 ")), Some(TEXT_PLAIN_UTF_8));
+    }
+}
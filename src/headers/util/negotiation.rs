@@ -0,0 +1,294 @@
+//! Proactive content negotiation ( RFC 9110 §12 )
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response, header};
+//! use whttp::util::mime;
+//!
+//! const MEDIA: &[mime::Mime] = &[mime::APPLICATION_JSON, mime::TEXT_HTML_UTF_8];
+//!
+//! fn handle(req: &Request) -> Response {
+//!     let mut negotiation = req.negotiation();
+//!
+//!     let Some(media) = negotiation.media(MEDIA) else {
+//!         return negotiation.not_acceptable()
+//!     };
+//!     let res = if *media == mime::APPLICATION_JSON {
+//!         Response::OK().with_json(["hello"])
+//!     } else {
+//!         Response::OK().with_html("<p>hello</p>")
+//!     };
+//!     res.with(header::Vary, negotiation.vary())
+//! }
+//!
+//! let req = Request::GET("/")
+//!     .with(header::Accept, "text/html, application/json;q=0.9");
+//! let res = handle(&req);
+//! assert_eq!(res.header(header::ContentType), Some("text/html; charset=UTF-8"));
+//! assert_eq!(res.header(header::Vary), Some("Accept"));
+//!
+//! let req = Request::GET("/")
+//!     .with(header::Accept, "image/*");
+//! let res = handle(&req);
+//! assert_eq!(res.status(), whttp::Status::NotAcceptable);
+//! assert_eq!(res.header(header::Vary), Some("Accept"));
+//! ```
+
+use crate::headers::{Headers, Header, standard, typed::Vary};
+use crate::Response;
+use super::mime::Mime;
+
+/// q-value in thousandths ( `1000` for `q=1` )
+pub type Quality = u16;
+
+/// Parse a list of `element;q=value` into elements and their q-values
+/// ( elements with an invalid q-value are skipped ).
+///
+/// Returned elements keep parameters other than `q` and after it, like
+/// `text/html;level=1` of `text/html;level=1;q=0.5`.
+pub fn parse_quality_list(list: &str) -> impl Iterator<Item = (&str, Quality)> {
+    split_list(list).filter_map(|element| {
+        let mut params = element.match_indices(';');
+        loop {
+            let Some((i, _)) = params.next() else {
+                return Some((element, 1000))
+            };
+            let param = element[i + 1..].trim_start();
+            let Some(q) = param.strip_prefix("q=").or_else(|| param.strip_prefix("Q=")) else {
+                continue
+            };
+            let q = q.split(';').next().unwrap().trim_end();
+            return quality(q).map(|q| (element[..i].trim_end(), q))
+        }
+    })
+}
+
+/// `qvalue = ( "0" [ "." 0*3DIGIT ] ) / ( "1" [ "." 0*3("0") ] )`
+fn quality(q: &str) -> Option<Quality> {
+    let (int, frac) = q.split_once('.').unwrap_or((q, ""));
+    if frac.len() > 3 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None
+    }
+    let frac = frac.bytes().chain(std::iter::repeat(b'0')).take(3)
+        .fold(0, |q, b| q * 10 + (b - b'0') as Quality);
+    match int {
+        "0" => Some(frac),
+        "1" if frac == 0 => Some(1000),
+        _ => None
+    }
+}
+
+/// split by `,` outside quoted-strings, skipping empty elements
+pub(super) fn split_list(list: &str) -> impl Iterator<Item = &str> {
+    let mut rest = list;
+    std::iter::from_fn(move || loop {
+        if rest.is_empty() {return None}
+
+        let (mut quoted, mut escaped) = (false, false);
+        let end = rest.bytes().position(|b| {
+            match b {
+                _ if escaped => escaped = false,
+                b'\\' if quoted => escaped = true,
+                b'"' => quoted = !quoted,
+                b',' if !quoted => return true,
+                _ => ()
+            }
+            false
+        }).unwrap_or(rest.len());
+
+        let element = rest[..end].trim();
+        rest = rest.get(end + 1..).unwrap_or_default();
+        if !element.is_empty() {return Some(element)}
+    })
+}
+
+/// Pick the best of `offers` in preference of server ( earlier is better )
+/// by the q-value of the most specific matching range for each offer.
+fn best<'o, O: ?Sized>(
+    offers:  impl IntoIterator<Item = &'o O>,
+    quality: impl Fn(&O) -> Quality,
+) -> Option<&'o O> {
+    let mut best = None;
+    for offer in offers {
+        let q = quality(offer);
+        if q > 0 && !matches!(best, Some((_, b)) if b >= q) {
+            best = Some((offer, q))
+        }
+    }
+    best.map(|(offer, _)| offer)
+}
+
+/*=====================================================*/
+
+/// `None` for `Accept` not accepting any of `offers`.
+/// Missing `Accept` accepts any media type.
+pub fn media_type<'o>(accept: Option<&str>, offers: &'o [Mime]) -> Option<&'o Mime> {
+    let Some(accept) = accept else {return offers.first()};
+
+    let ranges = parse_quality_list(accept)
+        .filter_map(|(range, q)| Mime::parse(range.to_owned()).map(|range| (range, q)))
+        .collect::<Vec<_>>();
+
+    best(offers, |offer| {
+        ranges.iter()
+            .filter(|(range, _)| offer.matches(range))
+            .max_by_key(|(range, _)| match (range.type_(), range.subtype()) {
+                ("*", "*") => 0,
+                (_,   "*") => 1,
+                _ => 2 + range.params().count()
+            })
+            .map_or(0, |(_, q)| *q)
+    })
+}
+
+/// `None` for `Accept-Language` not accepting any of `offers`.
+/// Missing `Accept-Language` accepts any language.
+///
+/// Language ranges are matched by *basic filtering* ( RFC 4647 §3.3.1 ):
+/// `en` matches `en` and `en-US`, but `en-US` doesn't match `en`.
+pub fn language<'o>(accept_language: Option<&str>, offers: &'o [&'o str]) -> Option<&'o str> {
+    let Some(accept_language) = accept_language else {return offers.first().copied()};
+
+    let ranges = parse_quality_list(accept_language).collect::<Vec<_>>();
+
+    best(offers.iter().copied(), |offer| {
+        ranges.iter()
+            .filter(|(range, _)| *range == "*" || (
+                offer.as_bytes().get(..range.len()).is_some_and(|o| o.eq_ignore_ascii_case(range.as_bytes())) &&
+                matches!(offer.as_bytes().get(range.len()), None | Some(b'-'))
+            ))
+            .max_by_key(|(range, _)| if *range == "*" {0} else {range.len()})
+            .map_or(0, |(_, q)| *q)
+    })
+}
+
+/// `None` for `Accept-Encoding` not accepting any of `offers`.
+/// Missing `Accept-Encoding` accepts any coding.
+///
+/// `identity` is acceptable unless excluded by `identity;q=0` or `*;q=0`.
+pub fn encoding<'o>(accept_encoding: Option<&str>, offers: &'o [&'o str]) -> Option<&'o str> {
+    let Some(accept_encoding) = accept_encoding else {return offers.first().copied()};
+
+    let codings = parse_quality_list(accept_encoding).collect::<Vec<_>>();
+
+    best(offers.iter().copied(), |offer| {
+        let specified = codings.iter().find(|(c, _)| c.eq_ignore_ascii_case(offer));
+        let any = codings.iter().find(|(c, _)| *c == "*");
+        match (specified, any) {
+            (Some((_, q)), _) => *q,
+            (None, Some((_, q))) => *q,
+            // least preferred, but acceptable
+            (None, None) => if offer.eq_ignore_ascii_case("identity") {1} else {0}
+        }
+    })
+}
+
+/*=====================================================*/
+
+/// Negotiation over headers of a request, recording which headers
+/// are used for `Vary`.
+pub struct Negotiation<'req> {
+    headers: &'req Headers,
+    vary:    Vec<&'static Header>,
+}
+
+impl<'req> Negotiation<'req> {
+    #[inline]
+    pub const fn new(headers: &'req Headers) -> Self {
+        Self { headers, vary: Vec::new() }
+    }
+
+    fn using(&mut self, header: &'static Header) -> Option<&'req str> {
+        if !self.vary.contains(&header) {
+            self.vary.push(header)
+        }
+        self.headers.get(header)
+    }
+
+    /// by `Accept`
+    pub fn media<'o>(&mut self, offers: &'o [Mime]) -> Option<&'o Mime> {
+        media_type(self.using(standard::Accept), offers)
+    }
+
+    /// by `Accept-Language`
+    pub fn language<'o>(&mut self, offers: &'o [&'o str]) -> Option<&'o str> {
+        language(self.using(standard::AcceptLanguage), offers)
+    }
+
+    /// by `Accept-Encoding`
+    pub fn encoding<'o>(&mut self, offers: &'o [&'o str]) -> Option<&'o str> {
+        encoding(self.using(standard::AcceptEncoding), offers)
+    }
+
+    /// `Vary` of the headers used so far
+    pub fn vary(&self) -> Vary {
+        Vary::Headers(self.vary.iter().map(|&h| String::from(&**h)).collect())
+    }
+
+    /// `406 Not Acceptable` with `Vary`
+    pub fn not_acceptable(&self) -> Response {
+        Response::NotAcceptable()
+            .with(standard::Vary, self.vary())
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::mime;
+
+    #[test]
+    fn test_parse_quality_list() {
+        assert_eq!(
+            parse_quality_list("text/html;level=1;q=0.5, text/*;Q=0, */*;q=1.000, a/b;q=1.5, , gzip;q=0.123;x=y").collect::<Vec<_>>(),
+            [("text/html;level=1", 500), ("text/*", 0), ("*/*", 1000), ("gzip", 123)]
+        );
+        assert_eq!(
+            parse_quality_list(r#"text/plain;format="a,b", en"#).collect::<Vec<_>>(),
+            [(r#"text/plain;format="a,b""#, 1000), ("en", 1000)]
+        );
+    }
+
+    #[test]
+    fn test_media_type() {
+        let offers = [mime::APPLICATION_JSON, mime::TEXT_HTML];
+        assert_eq!(media_type(None, &offers), Some(&mime::APPLICATION_JSON));
+        assert_eq!(media_type(Some("text/html"), &offers), Some(&mime::TEXT_HTML));
+        assert_eq!(media_type(Some("*/*;q=0.1, text/*"), &offers), Some(&mime::TEXT_HTML));
+        assert_eq!(media_type(Some("*/*"), &offers), Some(&mime::APPLICATION_JSON));
+        assert_eq!(media_type(Some("text/*, text/html;q=0"), &offers), None);
+        assert_eq!(media_type(Some("image/*"), &offers), None);
+        assert_eq!(media_type(Some("TEXT/HTML;q=0.5, application/json;q=0.4"), &offers), Some(&mime::TEXT_HTML));
+    }
+
+    #[test]
+    fn test_language() {
+        let offers = ["en-US", "ja"];
+        assert_eq!(language(Some("ja, en;q=0.8"), &offers), Some("ja"));
+        assert_eq!(language(Some("en"), &offers), Some("en-US"));
+        assert_eq!(language(Some("en-GB"), &offers), None);
+        assert_eq!(language(Some("*;q=0.5, en-us"), &offers), Some("en-US"));
+        assert_eq!(language(Some("*, ja;q=0"), &offers), Some("en-US"));
+        assert_eq!(language(Some("fr"), &offers), None);
+
+        /* range ending inside a non-ASCII offer */
+        assert_eq!(language(Some("de"), &["d\u{e9}"]), None);
+        assert_eq!(language(Some("en-g, en;q=0.5"), &["en-\u{e9}t"]), Some("en-\u{e9}t"));
+    }
+
+    #[test]
+    fn test_encoding() {
+        let offers = ["br", "gzip", "identity"];
+        assert_eq!(encoding(None, &offers), Some("br"));
+        assert_eq!(encoding(Some("gzip, deflate"), &offers), Some("gzip"));
+        assert_eq!(encoding(Some("gzip;q=0.5, br;q=0.8"), &offers), Some("br"));
+        assert_eq!(encoding(Some(""), &offers), Some("identity"));
+        assert_eq!(encoding(Some("deflate"), &offers), Some("identity"));
+        assert_eq!(encoding(Some("deflate, identity;q=0"), &offers), None);
+        assert_eq!(encoding(Some("deflate, *;q=0"), &offers), None);
+        assert_eq!(encoding(Some("*"), &offers), Some("br"));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::headers::{Header, Value, TypedHeader, standard};
+use super::sfv::{BareItem, Dictionary, Member};
+
+/// `Priority` ( RFC 9218 §4 )
+///
+/// A missing or invalid `Priority` means the default `u=3` without
+/// `i`, so requests are typically read as
+/// `req.headers().typed_get::<Priority>().unwrap_or_default()`.
+///
+/// *example.rs*
+/// ```
+/// use whttp::{Method, Request, Response, header};
+/// use whttp::util::Priority;
+///
+/// let req = Request::of(Method::GET, "/events")
+///     .with(header::Priority, "u=5, i");
+/// let priority = req.headers().typed_get::<Priority>().unwrap_or_default();
+/// assert_eq!(priority, Priority::new(5).incremental());
+///
+/// let res = Response::OK().with(header::Priority, Priority::new(1));
+/// assert_eq!(res.header(header::Priority), Some("u=1"));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Priority {
+    /// `u`, from 0 ( highest ) to 7 ( lowest )
+    pub urgency:     u8,
+    /// `i`, whether the response can be processed incrementally
+    pub incremental: bool,
+}
+
+impl Priority {
+    pub const DEFAULT_URGENCY: u8 = 3;
+
+    /// Panics if `urgency` is larger than 7.
+    #[inline]
+    pub const fn new(urgency: u8) -> Self {
+        if urgency > 7 {panic!("urgency must be 0..=7")}
+        Self { urgency, incremental: false }
+    }
+
+    #[inline]
+    pub const fn incremental(mut self) -> Self {
+        self.incremental = true;
+        self
+    }
+
+    /// `None` if `priority` is not a Structured Fields Dictionary.
+    /// Unknown parameters and ones of out-of-range values or unexpected
+    /// types are ignored as the default.
+    pub fn parse(priority: &str) -> Option<Self> {
+        let dictionary = Dictionary::parse(priority)?;
+        let bare = |key| match dictionary.get(key) {
+            Some(Member::Item(item)) => Some(&item.bare),
+            _ => None
+        };
+
+        let urgency = match bare("u") {
+            Some(BareItem::Integer(u @ 0..=7)) => *u as u8,
+            _ => Self::DEFAULT_URGENCY
+        };
+        let incremental = matches!(bare("i"), Some(BareItem::Boolean(true)));
+        Some(Self { urgency, incremental })
+    }
+}
+
+impl Default for Priority {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_URGENCY)
+    }
+}
+
+const _: () = {
+    impl std::fmt::Display for Priority {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "u={}", self.urgency)?;
+            if self.incremental {f.write_str(", i")?}
+            Ok(())
+        }
+    }
+
+    impl From<Priority> for Value {
+        #[inline]
+        fn from(priority: Priority) -> Self {
+            Value::from(priority.to_string())
+        }
+    }
+
+    impl TypedHeader for Priority {
+        const HEADER: &'static Header = standard::Priority;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            Priority::parse(value)
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        /* RFC 9218 §4 */
+        assert_eq!(Priority::parse("u=5"), Some(Priority::new(5)));
+        assert_eq!(Priority::parse("u=1, i"), Some(Priority::new(1).incremental()));
+        assert_eq!(Priority::parse("i, u=0"), Some(Priority::new(0).incremental()));
+        assert_eq!(Priority::parse("i=?0"), Some(Priority::default()));
+        assert_eq!(Priority::parse(""), Some(Priority::default()));
+
+        /* ignored as the default */
+        assert_eq!(Priority::parse("u=8, i"), Some(Priority::default().incremental()));
+        assert_eq!(Priority::parse("u=-1"), Some(Priority::default()));
+        assert_eq!(Priority::parse("u=1.0, i=1"), Some(Priority::default()));
+        assert_eq!(Priority::parse("u=(1 2)"), Some(Priority::default()));
+        assert_eq!(Priority::parse("u=2, x=abc, u=4"), Some(Priority::new(4)));
+
+        for invalid in ["u=", "U=1", "u=1,", "u=\"1\"x"] {
+            assert_eq!(Priority::parse(invalid), None, "{invalid}");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Priority::default().to_string(), "u=3");
+        assert_eq!(Priority::new(7).incremental().to_string(), "u=7, i");
+        for priority in [Priority::new(0), Priority::new(6).incremental()] {
+            assert_eq!(Priority::parse(&priority.to_string()), Some(priority));
+        }
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_urgency() {
+        let _ = Priority::new(8);
+    }
+}
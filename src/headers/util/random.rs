@@ -0,0 +1,19 @@
+//! non-cryptographic random numbers seeded by `RandomState`, which is
+//! randomly keyed by the OS per process
+//!
+//! Not for secrets, nonces or anything else that must be unpredictable;
+//! those are read from the OS's CSPRNG via `getrandom`.
+
+use std::hash::{BuildHasher, Hasher};
+use std::collections::hash_map::RandomState;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub(crate) fn u64() -> u64 {
+    static COUNT: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(COUNT.fetch_add(1, Ordering::Relaxed));
+    hasher.write_u128(SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_nanos()));
+    hasher.finish()
+}
@@ -0,0 +1,353 @@
+//! Range requests ( RFC 9110 §14 )
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Request, Response, Status, header};
+//! use whttp::util::{ETag, conditional::Validators, mime, range::Ranges};
+//!
+//! fn get_media(req: &Request, media: &'static [u8]) -> Response {
+//!     let validators = Validators::new().etag(ETag::of_payload(media));
+//!
+//!     let mut res = Response::OK();
+//!     res.set_ranged_payload(Ranges::of(req, &validators), mime::APPLICATION_OCTET_STREAM, media);
+//!     validators.apply(&mut res);
+//!     res
+//! }
+//!
+//! let res = get_media(&Request::GET("/media").with(header::Range, "bytes=2-4"), b"0123456789");
+//! assert_eq!(res.status(), Status::PartialContent);
+//! assert_eq!(res.header(header::ContentRange), Some("bytes 2-4/10"));
+//! assert_eq!(res.header(header::AcceptRanges), Some("bytes"));
+//! assert_eq!(res.payload(), Some(b"234".as_slice()));
+//!
+//! let res = get_media(&Request::GET("/media").with(header::Range, "bytes=10-"), b"0123456789");
+//! assert_eq!(res.status(), Status::RangeNotSatisfiable);
+//! assert_eq!(res.header(header::ContentRange), Some("bytes */10"));
+//!
+//! let res = get_media(&Request::GET("/media")
+//!     .with(header::Range, "bytes=2-4")
+//!     .with(header::IfRange, "\"outdated\""), b"0123456789");
+//! assert_eq!(res.status(), Status::OK);
+//! assert_eq!(res.payload(), Some(b"0123456789".as_slice()));
+//! ```
+
+use super::{conditional::{self, Validators}, mime};
+use crate::headers::{Header, Value, TypedHeader, standard, typed::ContentRange};
+use crate::{Request, Response, Status};
+use std::borrow::Cow;
+
+/// Ranges more than this in `Range` header are taken as abuse
+/// and the whole `Range` is ignored.
+pub const MAX_RANGE_SPECS: usize = 100;
+
+/// Satisfiable ranges more than this after coalescing are not served as
+/// `multipart/byteranges` and the whole representation is sent instead.
+pub const MAX_RANGES: usize = 16;
+
+/// `range-spec` of `Range: bytes=...`
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum RangeSpec {
+    /// `<first>-<last>`
+    FromTo(u64, u64),
+    /// `<first>-`
+    From(u64),
+    /// `-<suffix-length>`
+    Suffix(u64),
+}
+
+/// byte range in a representation, `last` inclusive
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ByteRange {
+    pub first: u64,
+    pub last:  u64,
+}
+impl ByteRange {
+    #[inline]
+    pub const fn length(&self) -> u64 {
+        self.last - self.first + 1
+    }
+}
+
+/// `Range: bytes=<range-spec>, ...`
+#[derive(Clone, PartialEq, Debug)]
+pub struct Ranges(pub Vec<RangeSpec>);
+
+/// result of [`Ranges::resolve`]
+#[derive(Clone, PartialEq, Debug)]
+pub enum Resolved {
+    /// respond with the whole representation, ignoring `Range`
+    Full,
+    /// respond `206 Partial Content` with these ranges in order
+    Partial(Vec<ByteRange>),
+    /// respond `416 Range Not Satisfiable`
+    Unsatisfiable,
+}
+
+impl Ranges {
+    /// `None` for other units than `bytes` or invalid syntax, when `Range` is to be ignored.
+    pub fn parse(range: &str) -> Option<Self> {
+        fn number(s: &str) -> Option<u64> {
+            (!s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())).then(|| s.parse().ok()).flatten()
+        }
+
+        let (unit, specs) = range.trim().split_once('=')?;
+        if !unit.eq_ignore_ascii_case("bytes") {return None}
+
+        let mut ranges = Vec::new();
+        for spec in specs.split(',') {
+            let spec = spec.trim();
+            if spec.is_empty() {continue}
+            if ranges.len() == MAX_RANGE_SPECS {return None}
+
+            let (first, last) = spec.split_once('-')?;
+            ranges.push(match (first, last) {
+                ("", suffix) => RangeSpec::Suffix(number(suffix)?),
+                (first, "") => RangeSpec::From(number(first)?),
+                (first, last) => {
+                    let (first, last) = (number(first)?, number(last)?);
+                    if first > last {return None}
+                    RangeSpec::FromTo(first, last)
+                }
+            });
+        }
+        (!ranges.is_empty()).then_some(Self(ranges))
+    }
+
+    /// `Range` of `req` to be processed, or `None` when the request has no
+    /// valid `Range` or its `If-Range` doesn't match `current`
+    pub fn of(req: &Request, current: &Validators) -> Option<Self> {
+        if !conditional::if_range(req, current) {return None}
+        Self::parse(req.header(standard::Range)?)
+    }
+
+    /// Resolve into satisfiable byte ranges of a representation of
+    /// `complete_length`, coalescing overlapping or adjacent ones.
+    pub fn resolve(&self, complete_length: u64) -> Resolved {
+        let mut ranges: Vec<ByteRange> = Vec::with_capacity(self.0.len());
+
+        for spec in &self.0 {
+            let mut range = match *spec {
+                RangeSpec::FromTo(first, _) |
+                RangeSpec::From(first) if first >= complete_length => continue,
+                RangeSpec::FromTo(first, last) => ByteRange { first, last: last.min(complete_length - 1) },
+                RangeSpec::From(first) => ByteRange { first, last: complete_length - 1 },
+                RangeSpec::Suffix(0) => continue,
+                RangeSpec::Suffix(_) if complete_length == 0 => continue,
+                RangeSpec::Suffix(n) => ByteRange { first: complete_length - n.min(complete_length), last: complete_length - 1 },
+            };
+
+            /* coalesce keeping the order of first appearance */
+            let mut position = None;
+            while let Some(i) = ranges.iter().position(|r| r.first <= range.last.saturating_add(1) && range.first <= r.last.saturating_add(1)) {
+                let r = ranges.remove(i);
+                range = ByteRange { first: r.first.min(range.first), last: r.last.max(range.last) };
+                position = Some(position.map_or(i, |p: usize| p.min(i)));
+            }
+            match position {
+                Some(i) => ranges.insert(i, range),
+                None => ranges.push(range)
+            }
+        }
+
+        match ranges.len() {
+            0 => Resolved::Unsatisfiable,
+            n if n > MAX_RANGES => Resolved::Full,
+            _ => Resolved::Partial(ranges)
+        }
+    }
+}
+
+impl std::fmt::Display for Ranges {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("bytes=")?;
+        for (i, spec) in self.0.iter().enumerate() {
+            if i > 0 {f.write_str(", ")?}
+            match spec {
+                RangeSpec::FromTo(first, last) => write!(f, "{first}-{last}")?,
+                RangeSpec::From(first) => write!(f, "{first}-")?,
+                RangeSpec::Suffix(n) => write!(f, "-{n}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+const _: () = {
+    impl From<Ranges> for Value {
+        #[inline]
+        fn from(ranges: Ranges) -> Self {
+            Value::from(ranges.to_string())
+        }
+    }
+
+    impl TypedHeader for Ranges {
+        const HEADER: &'static Header = standard::Range;
+
+        #[inline]
+        fn decode(value: &Value) -> Option<Self> {
+            Ranges::parse(value)
+        }
+
+        #[inline]
+        fn encode(&self) -> Value {
+            Value::from(self.to_string())
+        }
+    }
+};
+
+/// Set `200 OK`, `206 Partial Content` or `416 Range Not Satisfiable` to `res`
+/// with the parts read by `read`, always advertising `Accept-Ranges: bytes`.
+pub(crate) fn respond<E>(
+    res:             &mut Response,
+    ranges:          Option<Ranges>,
+    content_type:    Value,
+    complete_length: u64,
+    mut read:        impl FnMut(Option<ByteRange>) -> Result<Cow<'static, [u8]>, E>,
+) -> Result<(), E> {
+    use standard::{AcceptRanges, ContentRange as ContentRangeHeader};
+
+    res.set(AcceptRanges, "bytes");
+
+    let content_range = |range: Option<ByteRange>| ContentRange {
+        range: range.map(|r| (r.first, r.last)),
+        complete_length: Some(complete_length),
+    };
+
+    match ranges.map_or(Resolved::Full, |r| r.resolve(complete_length)) {
+        Resolved::Full => {
+            res.set_payload(content_type, read(None)?);
+        }
+        Resolved::Unsatisfiable => {
+            res.drop_body()
+                .set_status(Status::RangeNotSatisfiable)
+                .set(ContentRangeHeader, content_range(None));
+        }
+        Resolved::Partial(ranges) if ranges.len() == 1 => {
+            res.set_payload(content_type, read(Some(ranges[0]))?)
+                .set_status(Status::PartialContent)
+                .set(ContentRangeHeader, content_range(Some(ranges[0])));
+        }
+        Resolved::Partial(ranges) => {
+            let boundary = boundary();
+
+            let mut body = Vec::new();
+            for range in ranges {
+                body.extend_from_slice(b"--");
+                body.extend_from_slice(boundary.as_bytes());
+                body.extend_from_slice(b"\r\nContent-Type: ");
+                body.extend_from_slice(content_type.as_bytes());
+                body.extend_from_slice(b"\r\nContent-Range: ");
+                body.extend_from_slice(content_range(Some(range)).encode().as_bytes());
+                body.extend_from_slice(b"\r\n\r\n");
+                body.extend_from_slice(&read(Some(range))?);
+                body.extend_from_slice(b"\r\n");
+            }
+            body.extend_from_slice(b"--");
+            body.extend_from_slice(boundary.as_bytes());
+            body.extend_from_slice(b"--\r\n");
+
+            let content_type = mime::Mime::parse(format!("{}; boundary={boundary}", mime::MULTIPART_BYTERANGES)).unwrap();
+            res.set_payload(content_type, body)
+                .set_status(Status::PartialContent)
+                .set(ContentRangeHeader, None);
+        }
+    }
+    Ok(())
+}
+
+/// boundary unlikely to appear in the parts
+fn boundary() -> String {
+    format!("whttp-byteranges-{:016x}", super::random::u64())
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Ranges::parse("bytes=0-499"), Some(Ranges(vec![RangeSpec::FromTo(0, 499)])));
+        assert_eq!(Ranges::parse("Bytes=500-, -200 ,, 0-0"), Some(Ranges(vec![RangeSpec::From(500), RangeSpec::Suffix(200), RangeSpec::FromTo(0, 0)])));
+        for invalid in [
+            "bytes=", "bytes=500-499", "bytes=a-b", "bytes=-", "bytes 0-1", "items=0-1",
+            "bytes=0-1-2", "bytes=+1-2", "bytes=99999999999999999999-",
+        ] {
+            assert_eq!(Ranges::parse(invalid), None, "{invalid}");
+        }
+        assert_eq!(Ranges::parse(&format!("bytes={}", vec!["0-0"; MAX_RANGE_SPECS + 1].join(","))), None);
+        assert_eq!(Ranges::parse("bytes=500-, -200").unwrap().to_string(), "bytes=500-, -200");
+    }
+
+    #[test]
+    fn test_resolve() {
+        let resolve = |range: &str, len| Ranges::parse(range).unwrap().resolve(len);
+        let partial = |ranges: &[(u64, u64)]| Resolved::Partial(ranges.iter().map(|&(first, last)| ByteRange { first, last }).collect());
+
+        assert_eq!(resolve("bytes=0-499", 10000), partial(&[(0, 499)]));
+        assert_eq!(resolve("bytes=9500-", 10000), partial(&[(9500, 9999)]));
+        assert_eq!(resolve("bytes=-500", 10000), partial(&[(9500, 9999)]));
+        assert_eq!(resolve("bytes=-50000", 10000), partial(&[(0, 9999)]));
+        assert_eq!(resolve("bytes=0-99999", 10000), partial(&[(0, 9999)]));
+
+        /* unsatisfiable ones are skipped */
+        assert_eq!(resolve("bytes=10000-, 0-0", 10000), partial(&[(0, 0)]));
+        assert_eq!(resolve("bytes=10000-", 10000), Resolved::Unsatisfiable);
+        assert_eq!(resolve("bytes=-0", 10000), Resolved::Unsatisfiable);
+        assert_eq!(resolve("bytes=-1", 0), Resolved::Unsatisfiable);
+
+        /* coalescing keeps the order of first appearance */
+        assert_eq!(resolve("bytes=500-600, 0-99, 601-999, 100-199", 10000), partial(&[(500, 999), (0, 199)]));
+        assert_eq!(resolve("bytes=0-9, 20-29, 5-25", 10000), partial(&[(0, 29)]));
+        assert_eq!(resolve("bytes=0-0, -1, 0-", 3), partial(&[(0, 2)]));
+
+        /* too many ranges */
+        let many = (0..=MAX_RANGES).map(|i| format!("{}-{}", i * 10, i * 10)).collect::<Vec<_>>().join(",");
+        assert_eq!(resolve(&format!("bytes={many}"), 10000), Resolved::Full);
+    }
+
+    #[test]
+    fn test_multipart() {
+        let mut res = Response::OK();
+        res.set_ranged_payload(Ranges::parse("bytes=0-1, 5-"), mime::TEXT_PLAIN, b"0123456789".as_slice());
+        assert_eq!(res.status(), Status::PartialContent);
+        assert_eq!(res.header(header::ContentRange), None);
+
+        let content_type = res.headers().typed_get::<mime::Mime>().unwrap();
+        assert!(content_type.matches(&mime::MULTIPART_BYTERANGES));
+        let boundary = content_type.boundary().unwrap().into_owned();
+
+        assert_eq!(std::str::from_utf8(res.payload().unwrap()).unwrap(), format!("\
+            --{boundary}\r\n\
+            Content-Type: text/plain\r\n\
+            Content-Range: bytes 0-1/10\r\n\
+            \r\n\
+            01\r\n\
+            --{boundary}\r\n\
+            Content-Type: text/plain\r\n\
+            Content-Range: bytes 5-9/10\r\n\
+            \r\n\
+            56789\r\n\
+            --{boundary}--\r\n\
+        "));
+        assert_eq!(res.header(header::ContentLength), Some(&*res.payload().unwrap().len().to_string()));
+    }
+
+    #[test]
+    fn test_ranged_reader() {
+        let mut res = Response::OK();
+        res.set_ranged_reader(Ranges::parse("bytes=-3"), mime::APPLICATION_OCTET_STREAM, std::io::Cursor::new(b"0123456789")).unwrap();
+        assert_eq!(res.status(), Status::PartialContent);
+        assert_eq!(res.header(header::ContentRange), Some("bytes 7-9/10"));
+        assert_eq!(res.payload(), Some(b"789".as_slice()));
+
+        let mut res = Response::OK();
+        res.set_ranged_reader(None, mime::APPLICATION_OCTET_STREAM, std::io::Cursor::new(b"0123456789")).unwrap();
+        assert_eq!(res.status(), Status::OK);
+        assert_eq!(res.payload(), Some(b"0123456789".as_slice()));
+        assert_eq!(res.header(header::AcceptRanges), Some("bytes"));
+    }
+}
@@ -0,0 +1,186 @@
+//! preset of security-related response headers
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Response, header};
+//! use whttp::util::csp::{self, Csp, Nonce};
+//! use whttp::util::security::SecurityHeaders;
+//!
+//! let security = SecurityHeaders {
+//!     csp: Some(Csp::new()
+//!         .default_src([csp::SELF])
+//!         .script_src([csp::NONCE, csp::STRICT_DYNAMIC])
+//!         .object_src([csp::NONE])),
+//!     ..SecurityHeaders::new()
+//! };
+//!
+//! let nonce = Nonce::new();
+//! let mut res = Response::OK()
+//!     .with(header::ReferrerPolicy, "same-origin")
+//!     .with_html(format!(r#"<script nonce="{nonce}">run()</script>"#));
+//! security.apply(&mut res, Some(&nonce));
+//!
+//! assert_eq!(res.header(header::XContentTypeOptions), Some("nosniff"));
+//! assert_eq!(res.header(header::StrictTransportSecurity), Some("max-age=31536000; includeSubDomains"));
+//! /* headers already set are kept */
+//! assert_eq!(res.header(header::ReferrerPolicy), Some("same-origin"));
+//! ```
+
+use super::{Hsts, csp::{self, Csp, Nonce}};
+use crate::{Response, Header};
+use crate::headers::standard::{
+    ContentSecurityPolicy, ContentSecurityPolicyReportOnly,
+    StrictTransportSecurity, XFrameOptions, XContentTypeOptions, ReferrerPolicy,
+    CrossOriginOpenerPolicy, CrossOriginEmbedderPolicy, CrossOriginResourcePolicy,
+};
+
+/// Security headers set to responses unless already set. `None` or `false`
+/// for each field disables the header.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SecurityHeaders {
+    /// `Content-Security-Policy`, or `-Report-Only`
+    pub csp:                          Option<Csp>,
+    /// `Strict-Transport-Security`, ignored by browsers over plain HTTP
+    pub hsts:                         Option<Hsts>,
+    /// `X-Frame-Options`, superseded by `frame-ancestors` of CSP
+    pub frame_options:                Option<&'static str>,
+    /// `X-Content-Type-Options: nosniff`
+    pub nosniff:                      bool,
+    pub referrer_policy:              Option<&'static str>,
+    pub cross_origin_opener_policy:   Option<&'static str>,
+    pub cross_origin_embedder_policy: Option<&'static str>,
+    pub cross_origin_resource_policy: Option<&'static str>,
+}
+
+impl SecurityHeaders {
+    /// Defaults that break few sites:
+    ///
+    /// - `Content-Security-Policy: default-src 'self'; base-uri 'self'; form-action 'self'; frame-ancestors 'self'; object-src 'none'; script-src-attr 'none'; upgrade-insecure-requests`
+    /// - `Strict-Transport-Security: max-age=31536000; includeSubDomains`
+    /// - `X-Frame-Options: SAMEORIGIN`
+    /// - `X-Content-Type-Options: nosniff`
+    /// - `Referrer-Policy: strict-origin-when-cross-origin`
+    /// - `Cross-Origin-Opener-Policy: same-origin`
+    /// - `Cross-Origin-Resource-Policy: same-origin`
+    ///
+    /// `Cross-Origin-Embedder-Policy` is not set by default since
+    /// `require-corp` blocks cross-origin resources not opting in.
+    pub fn new() -> Self {
+        Self {
+            csp: Some(Csp::new()
+                .default_src([csp::SELF])
+                .base_uri([csp::SELF])
+                .form_action([csp::SELF])
+                .frame_ancestors([csp::SELF])
+                .object_src([csp::NONE])
+                .script_src_attr([csp::NONE])
+                .upgrade_insecure_requests()
+            ),
+            hsts:                         Some(Hsts::new(31536000).include_subdomains()),
+            frame_options:                Some("SAMEORIGIN"),
+            nosniff:                      true,
+            referrer_policy:              Some("strict-origin-when-cross-origin"),
+            cross_origin_opener_policy:   Some("same-origin"),
+            cross_origin_embedder_policy: None,
+            cross_origin_resource_policy: Some("same-origin"),
+        }
+    }
+
+    /// no headers, to be filled by struct update
+    pub const fn none() -> Self {
+        Self {
+            csp:                          None,
+            hsts:                         None,
+            frame_options:                None,
+            nosniff:                      false,
+            referrer_policy:              None,
+            cross_origin_opener_policy:   None,
+            cross_origin_embedder_policy: None,
+            cross_origin_resource_policy: None,
+        }
+    }
+
+    /// Set the headers to `res` except for ones already set, with
+    /// `nonce` for [`csp::NONCE`] in the CSP.
+    pub fn apply(&self, res: &mut Response, nonce: Option<&Nonce>) {
+        fn set_default(res: &mut Response, header: &Header, value: Option<&'static str>) {
+            if let Some(value) = value {
+                if res.header(header).is_none() {
+                    res.set(header, value);
+                }
+            }
+        }
+
+        if let Some(csp) = &self.csp {
+            let header = if csp.is_report_only() {ContentSecurityPolicyReportOnly} else {ContentSecurityPolicy};
+            if res.header(header).is_none() {
+                csp.apply(res, nonce);
+            }
+        }
+        if let Some(hsts) = self.hsts {
+            if res.header(StrictTransportSecurity).is_none() {
+                res.set(StrictTransportSecurity, hsts);
+            }
+        }
+        set_default(res, XFrameOptions, self.frame_options);
+        set_default(res, XContentTypeOptions, self.nosniff.then_some("nosniff"));
+        set_default(res, ReferrerPolicy, self.referrer_policy);
+        set_default(res, CrossOriginOpenerPolicy, self.cross_origin_opener_policy);
+        set_default(res, CrossOriginEmbedderPolicy, self.cross_origin_embedder_policy);
+        set_default(res, CrossOriginResourcePolicy, self.cross_origin_resource_policy);
+    }
+}
+
+impl Default for SecurityHeaders {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::header;
+
+    #[test]
+    fn test_defaults() {
+        let mut res = Response::OK();
+        SecurityHeaders::new().apply(&mut res, None);
+        assert_eq!(res.header(header::ContentSecurityPolicy), Some(
+            "default-src 'self'; base-uri 'self'; form-action 'self'; frame-ancestors 'self'; object-src 'none'; script-src-attr 'none'; upgrade-insecure-requests"
+        ));
+        assert_eq!(res.header(header::StrictTransportSecurity), Some("max-age=31536000; includeSubDomains"));
+        assert_eq!(res.header(header::XFrameOptions), Some("SAMEORIGIN"));
+        assert_eq!(res.header(header::XContentTypeOptions), Some("nosniff"));
+        assert_eq!(res.header(header::ReferrerPolicy), Some("strict-origin-when-cross-origin"));
+        assert_eq!(res.header(header::CrossOriginOpenerPolicy), Some("same-origin"));
+        assert_eq!(res.header(header::CrossOriginEmbedderPolicy), None);
+        assert_eq!(res.header(header::CrossOriginResourcePolicy), Some("same-origin"));
+    }
+
+    #[test]
+    fn test_configured() {
+        let security = SecurityHeaders {
+            csp:                          Some(Csp::new().script_src([csp::SELF, csp::NONCE]).report_only()),
+            hsts:                         Some(Hsts::new(600)),
+            cross_origin_embedder_policy: Some("require-corp"),
+            ..SecurityHeaders::none()
+        };
+        let nonce = Nonce::new();
+
+        let mut res = Response::OK()
+            .with(header::StrictTransportSecurity, "max-age=0")
+            .with(header::ContentSecurityPolicy, "default-src *");
+        security.apply(&mut res, Some(&nonce));
+        assert_eq!(res.header(header::StrictTransportSecurity), Some("max-age=0"));
+        assert_eq!(res.header(header::ContentSecurityPolicy), Some("default-src *"));
+        assert_eq!(res.header(header::ContentSecurityPolicyReportOnly).unwrap(), format!("script-src 'self' 'nonce-{nonce}'"));
+        assert_eq!(res.header(header::CrossOriginEmbedderPolicy), Some("require-corp"));
+        assert_eq!(res.header(header::XFrameOptions), None);
+        assert_eq!(res.header(header::XContentTypeOptions), None);
+    }
+}
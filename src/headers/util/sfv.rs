@@ -0,0 +1,897 @@
+//! Structured Field Values ( RFC 8941 )
+//!
+//! Items, Lists and Dictionaries are parsed strictly by the algorithms of
+//! RFC 8941 §4.2, and serialized into `Value` by §4.1. Parameters and
+//! dictionaries are ordered, where a duplicated key overwrites the value
+//! keeping the position of the first one.
+//!
+//! *example.rs*
+//! ```
+//! use whttp::{Response, header};
+//! use whttp::util::sfv::{self, BareItem, Dictionary, Item, List};
+//!
+//! let list = List::parse(r#"sugar, tea;milk, ("rye" "sourdough");toasted"#).unwrap();
+//! assert_eq!(list.0.len(), 3);
+//! assert_eq!(list.0[1].as_item().unwrap().param("milk"), Some(&BareItem::Boolean(true)));
+//!
+//! let dictionary = Dictionary::parse("u=2, i").unwrap();
+//! assert_eq!(dictionary.get("u").and_then(sfv::Member::as_item).map(|u| &u.bare), Some(&BareItem::Integer(2)));
+//!
+//! let hints = List(vec![
+//!     Item::new(BareItem::token("Sec-CH-UA-Model")).into(),
+//!     Item::new(BareItem::token("Sec-CH-UA-Platform")).into(),
+//! ]);
+//! let res = Response::OK().with(header::AcceptCH, hints);
+//! assert_eq!(res.header(header::AcceptCH), Some("Sec-CH-UA-Model, Sec-CH-UA-Platform"));
+//!
+//! let item = Item::new(BareItem::token("hit")).with_param("ttl", -5_i64);
+//! assert_eq!(item.serialize().as_deref(), Some("hit;ttl=-5"));
+//! ```
+
+use crate::headers::Value;
+use super::{auth::is_tchar, base64};
+
+/// `sf-integer` range
+const MAX_INTEGER: i64 = 999_999_999_999_999;
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum BareItem {
+    /// -999,999,999,999,999 ..= 999,999,999,999,999
+    Integer(i64),
+    Decimal(Decimal),
+    /// printable ASCII
+    String(String),
+    Token(String),
+    ByteSequence(Vec<u8>),
+    Boolean(bool),
+}
+
+/// `sf-decimal` as an integer of thousandths, having up to 12 digits
+/// in the integer part and 3 in the fractional part
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct Decimal(i64);
+
+impl Decimal {
+    #[inline]
+    pub const fn from_thousandths(thousandths: i64) -> Self {
+        Self(thousandths)
+    }
+
+    #[inline]
+    pub const fn thousandths(&self) -> i64 {
+        self.0
+    }
+
+    /// rounded to 3 fractional digits by round-half-to-even on the
+    /// shortest decimal representation of `f`, `None` for non-finite
+    /// or too large ones
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {return None}
+
+        let repr = format!("{}", f.abs());
+        let (int, frac) = repr.split_once('.').unwrap_or((&repr, ""));
+        let digit = |i: usize| frac.as_bytes().get(i).map_or(0, |b| (b - b'0') as i64);
+
+        let mut thousandths = int.parse::<i64>().ok()?.checked_mul(1000)?
+            + digit(0) * 100 + digit(1) * 10 + digit(2);
+        let rest = frac.get(3..).unwrap_or_default();
+        if rest.bytes().any(|b| b != b'0') {
+            let above_half = rest.as_bytes()[0] > b'5' || (rest.as_bytes()[0] == b'5' && rest[1..].bytes().any(|b| b != b'0'));
+            let half = rest.as_bytes()[0] == b'5' && !above_half;
+            if above_half || (half && thousandths % 2 == 1) {
+                thousandths += 1
+            }
+        }
+
+        (thousandths < 1_000_000_000_000_000).then_some(Self(if f < 0. {-thousandths} else {thousandths}))
+    }
+
+    #[inline]
+    pub fn as_f64(&self) -> f64 {
+        self.0 as f64 / 1000.
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.0 < 0 {f.write_str("-")?}
+        let (int, frac) = (self.0.unsigned_abs() / 1000, self.0.unsigned_abs() % 1000);
+        match frac {
+            _ if frac % 100 == 0 => write!(f, "{int}.{}", frac / 100),
+            _ if frac % 10 == 0  => write!(f, "{int}.{:02}", frac / 10),
+            _                    => write!(f, "{int}.{frac:03}")
+        }
+    }
+}
+
+/// parameters in order
+pub type Parameters = Vec<(String, BareItem)>;
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Item {
+    pub bare:   BareItem,
+    pub params: Parameters,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct InnerList {
+    pub items:  Vec<Item>,
+    pub params: Parameters,
+}
+
+/// member of List or Dictionary
+#[derive(Clone, PartialEq, Debug)]
+pub enum Member {
+    Item(Item),
+    InnerList(InnerList),
+}
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct List(pub Vec<Member>);
+
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Dictionary(pub Vec<(String, Member)>);
+
+impl BareItem {
+    /// Panics if `token` is not a valid `sf-token`.
+    pub fn token(token: impl Into<String>) -> Self {
+        let token = token.into();
+        if !is_token(&token) {panic!("invalid sf-token")}
+        Self::Token(token)
+    }
+
+    #[inline]
+    pub const fn as_integer(&self) -> Option<i64> {
+        match self {Self::Integer(i) => Some(*i), _ => None}
+    }
+    #[inline]
+    pub const fn as_decimal(&self) -> Option<Decimal> {
+        match self {Self::Decimal(d) => Some(*d), _ => None}
+    }
+    /// content of `String`
+    #[inline]
+    pub fn as_str(&self) -> Option<&str> {
+        match self {Self::String(s) => Some(s), _ => None}
+    }
+    #[inline]
+    pub fn as_token(&self) -> Option<&str> {
+        match self {Self::Token(t) => Some(t), _ => None}
+    }
+    #[inline]
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {Self::ByteSequence(b) => Some(b), _ => None}
+    }
+    #[inline]
+    pub const fn as_bool(&self) -> Option<bool> {
+        match self {Self::Boolean(b) => Some(*b), _ => None}
+    }
+}
+
+const _: () = {
+    impl From<i64> for BareItem {
+        #[inline]
+        fn from(i: i64) -> Self {Self::Integer(i)}
+    }
+    impl From<Decimal> for BareItem {
+        #[inline]
+        fn from(d: Decimal) -> Self {Self::Decimal(d)}
+    }
+    impl From<bool> for BareItem {
+        #[inline]
+        fn from(b: bool) -> Self {Self::Boolean(b)}
+    }
+    impl From<&str> for BareItem {
+        #[inline]
+        fn from(s: &str) -> Self {Self::String(s.into())}
+    }
+    impl From<String> for BareItem {
+        #[inline]
+        fn from(s: String) -> Self {Self::String(s)}
+    }
+    impl From<Vec<u8>> for BareItem {
+        #[inline]
+        fn from(b: Vec<u8>) -> Self {Self::ByteSequence(b)}
+    }
+};
+
+impl Item {
+    #[inline]
+    pub fn new(bare: impl Into<BareItem>) -> Self {
+        Self { bare: bare.into(), params: Vec::new() }
+    }
+
+    /// Set the parameter `key`, overwriting the existing one
+    #[inline]
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<BareItem>) -> Self {
+        set(&mut self.params, key.into(), value.into());
+        self
+    }
+
+    #[inline]
+    pub fn param(&self, key: &str) -> Option<&BareItem> {
+        get(&self.params, key)
+    }
+
+    pub fn parse(input: &str) -> Option<Self> {
+        Parser::new(input)?.top_level(Parser::item)
+    }
+
+    /// `None` if anything is out of the range of Structured Fields
+    pub fn serialize(&self) -> Option<String> {
+        let mut output = String::new();
+        serialize_item(self, &mut output)?;
+        Some(output)
+    }
+}
+
+impl InnerList {
+    #[inline]
+    pub fn new(items: impl IntoIterator<Item = Item>) -> Self {
+        Self { items: items.into_iter().collect(), params: Vec::new() }
+    }
+
+    /// Set the parameter `key`, overwriting the existing one
+    #[inline]
+    pub fn with_param(mut self, key: impl Into<String>, value: impl Into<BareItem>) -> Self {
+        set(&mut self.params, key.into(), value.into());
+        self
+    }
+
+    #[inline]
+    pub fn param(&self, key: &str) -> Option<&BareItem> {
+        get(&self.params, key)
+    }
+}
+
+impl Member {
+    #[inline]
+    pub const fn as_item(&self) -> Option<&Item> {
+        match self {Self::Item(item) => Some(item), _ => None}
+    }
+
+    #[inline]
+    pub const fn as_inner_list(&self) -> Option<&InnerList> {
+        match self {Self::InnerList(list) => Some(list), _ => None}
+    }
+
+    #[inline]
+    pub fn params(&self) -> &Parameters {
+        match self {Self::Item(item) => &item.params, Self::InnerList(list) => &list.params}
+    }
+}
+
+impl List {
+    /// an empty field parses as an empty List
+    pub fn parse(input: &str) -> Option<Self> {
+        Parser::new(input)?.top_level(Parser::list)
+    }
+
+    /// `None` if anything is out of the range of Structured Fields.
+    /// An empty List serializes as an empty string, though the field
+    /// should not be sent in that case.
+    pub fn serialize(&self) -> Option<String> {
+        let mut output = String::new();
+        for (i, member) in self.0.iter().enumerate() {
+            if i > 0 {output.push_str(", ")}
+            serialize_member(member, &mut output)?;
+        }
+        Some(output)
+    }
+}
+
+impl Dictionary {
+    /// an empty field parses as an empty Dictionary
+    pub fn parse(input: &str) -> Option<Self> {
+        Parser::new(input)?.top_level(Parser::dictionary)
+    }
+
+    /// `None` if anything is out of the range of Structured Fields.
+    /// An empty Dictionary serializes as an empty string, though the
+    /// field should not be sent in that case.
+    pub fn serialize(&self) -> Option<String> {
+        let mut output = String::new();
+        for (i, (key, member)) in self.0.iter().enumerate() {
+            if i > 0 {output.push_str(", ")}
+            serialize_key(key, &mut output)?;
+            match member {
+                Member::Item(Item { bare: BareItem::Boolean(true), params }) => serialize_params(params, &mut output)?,
+                member => {output.push('='); serialize_member(member, &mut output)?}
+            }
+        }
+        Some(output)
+    }
+
+    #[inline]
+    pub fn get(&self, key: &str) -> Option<&Member> {
+        get(&self.0, key)
+    }
+
+    /// Set the member `key`, overwriting the existing one
+    #[inline]
+    pub fn insert(&mut self, key: impl Into<String>, member: impl Into<Member>) -> &mut Self {
+        set(&mut self.0, key.into(), member.into());
+        self
+    }
+}
+
+fn get<'m, T>(map: &'m [(String, T)], key: &str) -> Option<&'m T> {
+    map.iter().find_map(|(k, v)| (k == key).then_some(v))
+}
+
+fn set<T>(map: &mut Vec<(String, T)>, key: String, value: T) {
+    match map.iter_mut().find(|(k, _)| *k == key) {
+        Some(entry) => entry.1 = value,
+        None => map.push((key, value))
+    }
+}
+
+const _: () = {
+    impl From<Item> for Member {
+        #[inline]
+        fn from(item: Item) -> Self {Self::Item(item)}
+    }
+    impl From<InnerList> for Member {
+        #[inline]
+        fn from(list: InnerList) -> Self {Self::InnerList(list)}
+    }
+
+    /// Panics if `item` can't be serialized.
+    impl From<Item> for Value {
+        fn from(item: Item) -> Self {
+            Value::from(item.serialize().expect("invalid Structured Field"))
+        }
+    }
+    /// Panics if `list` can't be serialized.
+    impl From<List> for Value {
+        fn from(list: List) -> Self {
+            Value::from(list.serialize().expect("invalid Structured Field"))
+        }
+    }
+    /// Panics if `dictionary` can't be serialized.
+    impl From<Dictionary> for Value {
+        fn from(dictionary: Dictionary) -> Self {
+            Value::from(dictionary.serialize().expect("invalid Structured Field"))
+        }
+    }
+};
+
+/*===== parsing ( RFC 8941 §4.2 ) =====*/
+
+struct Parser<'i> {
+    input: &'i [u8],
+}
+
+impl<'i> Parser<'i> {
+    /// `None` for non-ASCII input
+    fn new(input: &'i str) -> Option<Self> {
+        input.is_ascii().then_some(Self { input: input.as_bytes() })
+    }
+
+    #[inline]
+    fn peek(&self) -> Option<u8> {
+        self.input.first().copied()
+    }
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        let (first, rest) = self.input.split_first()?;
+        self.input = rest;
+        Some(*first)
+    }
+
+    #[inline]
+    fn skip_sp(&mut self) {
+        while self.peek() == Some(b' ') {self.input = &self.input[1..]}
+    }
+
+    #[inline]
+    fn skip_ows(&mut self) {
+        while matches!(self.peek(), Some(b' ' | b'\t')) {self.input = &self.input[1..]}
+    }
+
+    fn top_level<T>(mut self, parse: fn(&mut Self) -> Option<T>) -> Option<T> {
+        self.skip_sp();
+        let output = parse(&mut self)?;
+        self.skip_sp();
+        self.input.is_empty().then_some(output)
+    }
+
+    /// `sf-list`, after which `input` is empty unless failed
+    fn list(&mut self) -> Option<List> {
+        let mut members = Vec::new();
+        while !self.input.is_empty() {
+            members.push(self.member()?);
+            self.skip_ows();
+            if self.input.is_empty() {break}
+            if self.next()? != b',' {return None}
+            self.skip_ows();
+            if self.input.is_empty() {return None}
+        }
+        Some(List(members))
+    }
+
+    /// `sf-dictionary`, after which `input` is empty unless failed
+    fn dictionary(&mut self) -> Option<Dictionary> {
+        let mut dictionary = Dictionary::default();
+        while !self.input.is_empty() {
+            let key = self.key()?;
+            let member = if self.peek() == Some(b'=') {
+                self.next();
+                self.member()?
+            } else {
+                Member::Item(Item { bare: BareItem::Boolean(true), params: self.params()? })
+            };
+            set(&mut dictionary.0, key, member);
+
+            self.skip_ows();
+            if self.input.is_empty() {break}
+            if self.next()? != b',' {return None}
+            self.skip_ows();
+            if self.input.is_empty() {return None}
+        }
+        Some(dictionary)
+    }
+
+    fn member(&mut self) -> Option<Member> {
+        if self.peek() == Some(b'(') {
+            self.inner_list().map(Member::InnerList)
+        } else {
+            self.item().map(Member::Item)
+        }
+    }
+
+    fn inner_list(&mut self) -> Option<InnerList> {
+        if self.next()? != b'(' {return None}
+        let mut items = Vec::new();
+        loop {
+            self.skip_sp();
+            if self.peek()? == b')' {
+                self.next();
+                return Some(InnerList { items, params: self.params()? })
+            }
+            items.push(self.item()?);
+            if !matches!(self.peek()?, b' ' | b')') {return None}
+        }
+    }
+
+    fn item(&mut self) -> Option<Item> {
+        Some(Item { bare: self.bare_item()?, params: self.params()? })
+    }
+
+    fn bare_item(&mut self) -> Option<BareItem> {
+        match self.peek()? {
+            b'-' | b'0'..=b'9' => self.number(),
+            b'"' => self.string(),
+            b'*' | b'A'..=b'Z' | b'a'..=b'z' => self.token(),
+            b':' => self.byte_sequence(),
+            b'?' => self.boolean(),
+            _ => None
+        }
+    }
+
+    fn params(&mut self) -> Option<Parameters> {
+        let mut params = Vec::new();
+        while self.peek() == Some(b';') {
+            self.next();
+            self.skip_sp();
+            let key = self.key()?;
+            let value = if self.peek() == Some(b'=') {
+                self.next();
+                self.bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+            set(&mut params, key, value);
+        }
+        Some(params)
+    }
+
+    fn key(&mut self) -> Option<String> {
+        if !matches!(self.peek()?, b'a'..=b'z' | b'*') {return None}
+        let len = self.input.iter().position(|b| !is_key_char(*b)).unwrap_or(self.input.len());
+        let (key, rest) = self.input.split_at(len);
+        self.input = rest;
+        // SAFETY: `input` is ASCII
+        Some(unsafe {std::str::from_utf8_unchecked(key)}.into())
+    }
+
+    fn number(&mut self) -> Option<BareItem> {
+        let negative = self.peek() == Some(b'-');
+        if negative {self.next();}
+        if !self.peek()?.is_ascii_digit() {return None}
+
+        let (mut digits, mut dot) = (0, None);
+        for (i, b) in self.input.iter().enumerate() {
+            match b {
+                b'0'..=b'9' => digits = i + 1,
+                b'.' if dot.is_none() => {
+                    if i > 12 {return None}
+                    dot = Some(i)
+                }
+                _ => break
+            }
+            if (dot.is_none() && i + 1 > 15) || i + 1 > 16 {return None}
+        }
+        let len = match dot {
+            Some(dot) => {
+                if digits <= dot + 1 || digits - dot - 1 > 3 {return None}
+                digits
+            }
+            None => digits
+        };
+        let (number, rest) = self.input.split_at(len);
+        self.input = rest;
+        // SAFETY: `number` consists of ASCII digits and `.`
+        let number = unsafe {std::str::from_utf8_unchecked(number)};
+
+        match number.split_once('.') {
+            None => {
+                let i = number.parse::<i64>().ok()?;
+                Some(BareItem::Integer(if negative {-i} else {i}))
+            }
+            Some((int, frac)) => {
+                let thousandths = int.parse::<i64>().ok()? * 1000
+                    + frac.parse::<i64>().ok()? * 10_i64.pow(3 - frac.len() as u32);
+                Some(BareItem::Decimal(Decimal(if negative {-thousandths} else {thousandths})))
+            }
+        }
+    }
+
+    fn string(&mut self) -> Option<BareItem> {
+        if self.next()? != b'"' {return None}
+        let mut string = String::new();
+        loop {
+            match self.next()? {
+                b'\\' => match self.next()? {
+                    b @ (b'"' | b'\\') => string.push(b as char),
+                    _ => return None
+                },
+                b'"' => return Some(BareItem::String(string)),
+                b @ b' '..=b'~' => string.push(b as char),
+                _ => return None
+            }
+        }
+    }
+
+    fn token(&mut self) -> Option<BareItem> {
+        if !matches!(self.peek()?, b'*' | b'A'..=b'Z' | b'a'..=b'z') {return None}
+        let len = self.input.iter().position(|b| !is_token_char(*b)).unwrap_or(self.input.len());
+        let (token, rest) = self.input.split_at(len);
+        self.input = rest;
+        // SAFETY: `input` is ASCII
+        Some(BareItem::Token(unsafe {std::str::from_utf8_unchecked(token)}.into()))
+    }
+
+    fn byte_sequence(&mut self) -> Option<BareItem> {
+        if self.next()? != b':' {return None}
+        let len = self.input.iter().position(|b| *b == b':')?;
+        let (encoded, rest) = self.input.split_at(len);
+        self.input = &rest[1..];
+        if !encoded.iter().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=')) {return None}
+        // SAFETY: `input` is ASCII
+        base64::decode(unsafe {std::str::from_utf8_unchecked(encoded)}).map(BareItem::ByteSequence)
+    }
+
+    fn boolean(&mut self) -> Option<BareItem> {
+        if self.next()? != b'?' {return None}
+        match self.next()? {
+            b'1' => Some(BareItem::Boolean(true)),
+            b'0' => Some(BareItem::Boolean(false)),
+            _ => None
+        }
+    }
+}
+
+#[inline]
+const fn is_key_char(b: u8) -> bool {
+    matches!(b, b'a'..=b'z' | b'0'..=b'9' | b'_' | b'-' | b'.' | b'*')
+}
+
+#[inline]
+fn is_token_char(b: u8) -> bool {
+    is_tchar(b) || matches!(b, b':' | b'/')
+}
+
+fn is_token(s: &str) -> bool {
+    matches!(s.as_bytes().first(), Some(b'*' | b'A'..=b'Z' | b'a'..=b'z'))
+        && s.bytes().all(is_token_char)
+}
+
+/*===== serialization ( RFC 8941 §4.1 ) =====*/
+
+fn serialize_member(member: &Member, output: &mut String) -> Option<()> {
+    match member {
+        Member::Item(item) => serialize_item(item, output),
+        Member::InnerList(InnerList { items, params }) => {
+            output.push('(');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {output.push(' ')}
+                serialize_item(item, output)?;
+            }
+            output.push(')');
+            serialize_params(params, output)
+        }
+    }
+}
+
+fn serialize_item(Item { bare, params }: &Item, output: &mut String) -> Option<()> {
+    serialize_bare_item(bare, output)?;
+    serialize_params(params, output)
+}
+
+fn serialize_params(params: &Parameters, output: &mut String) -> Option<()> {
+    for (key, value) in params {
+        output.push(';');
+        serialize_key(key, output)?;
+        if *value != BareItem::Boolean(true) {
+            output.push('=');
+            serialize_bare_item(value, output)?;
+        }
+    }
+    Some(())
+}
+
+fn serialize_key(key: &str, output: &mut String) -> Option<()> {
+    if !matches!(key.as_bytes().first(), Some(b'a'..=b'z' | b'*')) || !key.bytes().all(is_key_char) {
+        return None
+    }
+    output.push_str(key);
+    Some(())
+}
+
+fn serialize_bare_item(bare: &BareItem, output: &mut String) -> Option<()> {
+    use std::fmt::Write;
+
+    match bare {
+        BareItem::Integer(i) => {
+            if !(-MAX_INTEGER..=MAX_INTEGER).contains(i) {return None}
+            write!(output, "{i}").ok()
+        }
+        BareItem::Decimal(d) => {
+            if d.0.unsigned_abs() >= 1_000_000_000_000_000 {return None}
+            write!(output, "{d}").ok()
+        }
+        BareItem::String(s) => {
+            if !s.bytes().all(|b| matches!(b, b' '..=b'~')) {return None}
+            output.push('"');
+            for c in s.chars() {
+                if matches!(c, '"' | '\\') {output.push('\\')}
+                output.push(c);
+            }
+            output.push('"');
+            Some(())
+        }
+        BareItem::Token(t) => {
+            if !is_token(t) {return None}
+            output.push_str(t);
+            Some(())
+        }
+        BareItem::ByteSequence(b) => {
+            output.push(':');
+            output.push_str(&base64::encode(b));
+            output.push(':');
+            Some(())
+        }
+        BareItem::Boolean(b) => {
+            output.push_str(if *b {"?1"} else {"?0"});
+            Some(())
+        }
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    //! Runs every `*.json` under `tests/structured-field-tests` and its
+    //! `serialisation-tests`, laid out as the HTTP WG's structured-field-tests
+    //! ( https://github.com/httpwg/structured-field-tests ) so that the files
+    //! of a checkout are copied there unchanged, except for the cases in `SKIPPED`.
+    //!
+    //! The files there now are NOT a copy of the upstream suite: they were
+    //! transcribed by hand and the `*-generated` ones regenerated after its
+    //! generators. They are to be replaced with the upstream files, with the
+    //! commit they are taken from recorded here.
+
+    use super::*;
+    use serde_json::Value as Json;
+
+    /// `(file, test name)` not run, where `*` is every test of the file
+    const SKIPPED: &[(&str, &str)] = &[
+        /* Date and Display String are of RFC 9651, not supported by RFC 8941 */
+        ("date.json",                               "*"),
+        ("display-string.json",                     "*"),
+        ("serialisation-tests/date.json",           "*"),
+        ("serialisation-tests/display-string.json", "*"),
+    ];
+
+    fn skipped(file: &str, name: &str) -> bool {
+        SKIPPED.iter().any(|&(f, n)| f == file && (n == "*" || n == name))
+    }
+
+    /// `(file, tests)` of every `*.json` in `dir` of the suite
+    fn suite(dir: &str) -> Vec<(String, Vec<Json>)> {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/structured-field-tests");
+        let mut files = std::fs::read_dir(root.join(dir)).unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|e| e == "json"))
+            .collect::<Vec<_>>();
+        files.sort();
+        assert!(!files.is_empty(), "no tests in `{dir}`");
+
+        files.into_iter().map(|path| {
+            let file = path.strip_prefix(&root).unwrap().to_str().unwrap().replace('\\', "/");
+            let tests = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            (file, tests)
+        }).collect()
+    }
+
+    #[derive(PartialEq, Debug)]
+    enum Field {
+        Item(Item),
+        List(List),
+        Dictionary(Dictionary),
+    }
+
+    impl Field {
+        fn parse(header_type: &str, input: &str) -> Option<Self> {
+            match header_type {
+                "item"       => Item::parse(input).map(Self::Item),
+                "list"       => List::parse(input).map(Self::List),
+                "dictionary" => Dictionary::parse(input).map(Self::Dictionary),
+                _ => unreachable!("unknown header_type `{header_type}`")
+            }
+        }
+
+        /// `None` if `expected` is out of the range of our types
+        fn from_json(header_type: &str, expected: &Json) -> Option<Self> {
+            match header_type {
+                "item"       => json_item(expected).map(Self::Item),
+                "list"       => expected.as_array().unwrap().iter()
+                    .map(json_member).collect::<Option<_>>().map(List).map(Self::List),
+                "dictionary" => expected.as_array().unwrap().iter()
+                    .map(|entry| Some((entry[0].as_str().unwrap().to_owned(), json_member(&entry[1])?)))
+                    .collect::<Option<_>>().map(Dictionary).map(Self::Dictionary),
+                _ => unreachable!("unknown header_type `{header_type}`")
+            }
+        }
+
+        fn serialize(&self) -> Option<String> {
+            match self {
+                Self::Item(item)             => item.serialize(),
+                Self::List(list)             => list.serialize(),
+                Self::Dictionary(dictionary) => dictionary.serialize(),
+            }
+        }
+    }
+
+    fn json_member(member: &Json) -> Option<Member> {
+        match &member[0] {
+            Json::Array(items) => Some(Member::InnerList(InnerList {
+                items:  items.iter().map(json_item).collect::<Option<_>>()?,
+                params: json_params(&member[1])?,
+            })),
+            _ => json_item(member).map(Member::Item)
+        }
+    }
+
+    fn json_item(item: &Json) -> Option<Item> {
+        Some(Item { bare: json_bare_item(&item[0])?, params: json_params(&item[1])? })
+    }
+
+    fn json_params(params: &Json) -> Option<Parameters> {
+        params.as_array().unwrap().iter()
+            .map(|param| Some((param[0].as_str().unwrap().to_owned(), json_bare_item(&param[1])?)))
+            .collect()
+    }
+
+    fn json_bare_item(bare: &Json) -> Option<BareItem> {
+        Some(match bare {
+            Json::Bool(b) => BareItem::Boolean(*b),
+            Json::String(s) => BareItem::String(s.clone()),
+            Json::Number(n) => match n.as_i64() {
+                Some(i) => BareItem::Integer(i),
+                None => BareItem::Decimal(Decimal::from_f64(n.as_f64().unwrap())?)
+            },
+            Json::Object(o) => match o["__type"].as_str().unwrap() {
+                "token"  => BareItem::Token(o["value"].as_str().unwrap().to_owned()),
+                "binary" => BareItem::ByteSequence(base32(o["value"].as_str().unwrap())),
+                other => unreachable!("unknown __type `{other}`")
+            },
+            other => unreachable!("unexpected bare item `{other}`")
+        })
+    }
+
+    /// RFC 4648 §6, only for the suite
+    fn base32(encoded: &str) -> Vec<u8> {
+        let (mut bytes, mut buffer, mut bits) = (Vec::new(), 0_u64, 0);
+        for b in encoded.trim_end_matches('=').bytes() {
+            buffer = buffer << 5 | match b {
+                b'A'..=b'Z' => b - b'A',
+                b'2'..=b'7' => b - b'2' + 26,
+                _ => unreachable!()
+            } as u64;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                bytes.push((buffer >> bits) as u8);
+            }
+        }
+        bytes
+    }
+
+    fn joined(lines: &Json) -> String {
+        lines.as_array().unwrap().iter().map(|line| line.as_str().unwrap()).collect::<Vec<_>>().join(", ")
+    }
+
+    #[test]
+    fn test_parsing_suite() {
+        for (file, tests) in suite("") {
+            for test in tests {
+                if skipped(&file, test["name"].as_str().unwrap()) {continue}
+                let name = format!("{file}: {}", test["name"].as_str().unwrap());
+                let header_type = test["header_type"].as_str().unwrap();
+                let parsed = Field::parse(header_type, &joined(&test["raw"]));
+
+                if test["must_fail"] == true {
+                    assert_eq!(parsed, None, "{name}");
+                    continue
+                }
+                let Some(parsed) = parsed else {
+                    assert!(test["can_fail"] == true, "{name}: failed to parse");
+                    continue
+                };
+                assert_eq!(Some(&parsed), Field::from_json(header_type, &test["expected"]).as_ref(), "{name}");
+
+                let canonical = test.get("canonical").unwrap_or(&test["raw"]);
+                assert_eq!(parsed.serialize(), Some(joined(canonical)), "{name}: serialization");
+            }
+        }
+    }
+
+    #[test]
+    fn test_serialisation_suite() {
+        for (file, tests) in suite("serialisation-tests") {
+            for test in tests {
+                if skipped(&file, test["name"].as_str().unwrap()) {continue}
+                let name = format!("{file}: {}", test["name"].as_str().unwrap());
+                let serialized = Field::from_json(test["header_type"].as_str().unwrap(), &test["expected"])
+                    .and_then(|field| field.serialize());
+
+                if test["must_fail"] == true {
+                    assert_eq!(serialized, None, "{name}");
+                } else {
+                    assert_eq!(serialized, Some(joined(&test["canonical"])), "{name}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_builders() {
+        let mut dictionary = Dictionary::default();
+        dictionary
+            .insert("u", Item::new(5_i64))
+            .insert("i", Item::new(true))
+            .insert("u", Item::new(2_i64));
+        assert_eq!(dictionary.serialize().as_deref(), Some("u=2, i"));
+        assert_eq!(dictionary.get("i").and_then(Member::as_item).and_then(|i| i.bare.as_bool()), Some(true));
+
+        let list = List(vec![
+            Item::new(BareItem::token("gzip")).with_param("q", Decimal::from_f64(0.5).unwrap()).into(),
+            InnerList::new([Item::new("a"), Item::new(b"hi".to_vec())]).with_param("x", false).into(),
+        ]);
+        assert_eq!(&*Value::from(list), r#"gzip;q=0.5, ("a" :aGk=:);x=?0"#);
+
+        assert_eq!(Decimal::from_thousandths(-1_500).to_string(), "-1.5");
+        assert_eq!(Decimal::from_f64(f64::NAN), None);
+        assert_eq!(Item::new("tab\there").serialize(), None);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_invalid_token() {
+        let _ = BareItem::token("not a token");
+    }
+}
@@ -30,10 +30,7 @@ const fn available(byte: &u8) -> bool {
 
 #[inline(always)]
 fn valid(bytes: &[u8]) -> bool {
-    for b in bytes {
-        if !available(b) {return false}
-    }
-    true
+    crate::scan::find_non_value(bytes).is_none()
 }
 
 const fn const_valid(bytes: &[u8]) -> bool {
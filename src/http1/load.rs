@@ -1,5 +1,5 @@
-use crate::{Request, Status, request::parse, io::Read};
-use crate::header::ContentLength;
+use crate::{Method, Request, Status, request::parse::{self, Scanner}, io::Read};
+use crate::header::{ContentLength, TransferEncoding};
 use std::{pin::Pin, io::ErrorKind, str::FromStr as _};
 
 const PAYLOAD_LIMIT: usize = 1 << 32;
@@ -15,7 +15,7 @@ pub async fn load(
     parse::clear(&mut req);
     let buf = parse::buf(req.as_mut());
 
-    match conn.read(&mut **buf).await {
+    let read = match conn.read(&mut **buf).await {
         Err(e) => return match e.kind() {
             ErrorKind::ConnectionReset => Ok(None),
             _ => Err((|_err| {
@@ -24,10 +24,10 @@ pub async fn load(
             })(e))
         },
         Ok(0) => return Ok(None),
-        _ => ()
-    }
+        Ok(n) => n
+    };
 
-    let mut r = byte_reader::Reader::new(unsafe {
+    let mut r = Scanner::new(unsafe {
         // lifetime trick.
         // SAFETY: `req.buf` is immutable after `parse::buf`
         std::mem::transmute(buf.as_slice())
@@ -35,32 +35,61 @@ pub async fn load(
 
     /* from here, SAFETY of `parse::*`: just self-referencing bytes of `req.buf` */
 
-    unsafe {parse::method(&mut req, r.read_while(|&b| b != b' '))}?;
+    unsafe {parse::method(&mut req, r.read_until(b' '))}?;
 
-    r.next_if(|&b| b == b' ').ok_or(Status::BadRequest)?;
+    r.consume(" ").ok_or(Status::BadRequest)?;
 
-    unsafe {parse::path(&mut req, r.read_while(|&b| !matches!(b, b' '|b'?')))}?;
+    if req.method() == Method::CONNECT {
+        unsafe {parse::authority(&mut req, r.read_until(b' '))}?;
+    } else {
+        unsafe {parse::path(&mut req, r.read_until_either(b' ', b'?'))}?;
 
-    if r.next_if(|&b| b == b'?').is_some() {
-        unsafe {parse::query(&mut req, r.read_while(|&b| b != b' '))}?;
+        if r.consume("?").is_some() {
+            unsafe {parse::query(&mut req, r.read_until(b' '))}?;
+        }
     }
 
-    r.next_if(|&b| b == b' ').ok_or(Status::BadRequest)?;
+    r.consume(" ").ok_or(Status::BadRequest)?;
 
     r.consume("HTTP/1.1\r\n").ok_or(Status::HTTPVersionNotSupported)?;
 
     while r.consume("\r\n").is_none() {
-        let name = r.read_while(|&b| b != b':');
+        let name = r.read_until(b':');
         r.consume(": ").ok_or(Status::BadRequest)?;
-        let value = r.read_while(|&b| b != b'\r');
+        let value = r.read_until(b'\r');
         r.consume("\r\n").ok_or(Status::BadRequest)?;
         unsafe {parse::header(&mut req, name, value)}?;
     }
 
+    if let Some(te) = req.header(TransferEncoding) {
+        if !te.rsplit(',').next().unwrap_or_default().trim().eq_ignore_ascii_case("chunked") {
+            return Err(if te.split(',').any(|t| t.trim().eq_ignore_ascii_case("chunked")) {
+                Status::BadRequest
+            } else {
+                Status::NotImplemented
+            })
+        }
+        if req.header(ContentLength).is_some() {
+            return Err(Status::BadRequest)
+        }
+
+        let buffered = &r.remaining()[..read.saturating_sub(r.index)];
+        load_chunked_body(req, conn, buffered).await?;
+        return Ok(Some(()))
+    }
+
+    let buffered = &r.remaining()[..read.saturating_sub(r.index)];
     match req.header(ContentLength).map(usize::from_str).transpose().map_err(|_| Status::BadRequest)? {
-        None | Some(0) => (),
+        None | Some(0) => {
+            parse::rest(&mut req, r.index..read);
+        }
         Some(PAYLOAD_LIMIT..) => return Err(Status::PayloadTooLarge),
-        Some(n) => load_body(req, conn, r.remaining(), n).await?
+        Some(n) => {
+            if n < buffered.len() {
+                parse::rest(&mut req, (r.index + n)..read);
+            }
+            load_body(req, conn, buffered, n).await?
+        }
     }
 
     Ok(Some(()))
@@ -75,8 +104,8 @@ async fn load_body(
 ) -> Result<(), Status> {
     let remaining_buf_len = remaining_buf.len();
 
-    if remaining_buf_len == 0 || remaining_buf[0] == 0 {
-        #[cfg(feature="DEBUG")] {println!("\n[load_body] case: remaining_buf.is_empty() || remaining_buf[0] == 0\n")}
+    if remaining_buf_len == 0 {
+        #[cfg(feature="DEBUG")] {println!("\n[load_body] case: remaining_buf.is_empty()\n")}
 
         let mut body = vec![0; content_length];
         conn.read_exact(&mut body).await.map_err(|_| Status::InternalServerError)?;
@@ -100,6 +129,103 @@ async fn load_body(
     Ok(())
 }
 
+/// Decode `Transfer-Encoding: chunked` body and its trailer section
+/// ( https://httpwg.org/specs/rfc9112.html#chunked.encoding ),
+/// starting from `buffered` bytes already read from `conn`.
+async fn load_chunked_body(
+    mut req:  Pin<&mut Request>,
+    conn:     &mut (impl Read + Unpin),
+    buffered: &[u8],
+) -> Result<(), Status> {
+    struct Chunked<'c, C> {
+        conn: &'c mut C,
+        data: Vec<u8>,
+        pos:  usize,
+    }
+    impl<'c, C: Read + Unpin> Chunked<'c, C> {
+        async fn fill(&mut self) -> Result<(), Status> {
+            let len = self.data.len();
+            self.data.resize(len + parse::BUF_SIZE, 0);
+            match self.conn.read(&mut self.data[len..]).await {
+                Ok(0) | Err(_) => Err(Status::BadRequest),
+                Ok(n) => {self.data.truncate(len + n); Ok(())}
+            }
+        }
+
+        async fn line(&mut self, limit: usize) -> Result<&[u8], Status> {
+            let mut checked = self.pos;
+            loop {
+                if let Some(lf) = self.data[checked..].iter().position(|&b| b == b'\n') {
+                    let (start, end) = (self.pos, checked + lf);
+                    self.pos = end + 1;
+                    return match self.data[start..end].strip_suffix(b"\r") {
+                        Some(line) => Ok(line),
+                        None => Err(Status::BadRequest)
+                    }
+                }
+                checked = self.data.len();
+                if checked - self.pos > limit {
+                    return Err(Status::BadRequest)
+                }
+                self.fill().await?;
+            }
+        }
+
+        async fn exact(&mut self, n: usize) -> Result<&[u8], Status> {
+            while self.data.len() - self.pos < n {
+                self.fill().await?;
+            }
+            let start = self.pos;
+            self.pos += n;
+            Ok(&self.data[start..self.pos])
+        }
+    }
+
+    let mut chunked = Chunked { conn, data: buffered.to_vec(), pos: 0 };
+    let mut body = Vec::new();
+
+    loop {
+        let size_line = chunked.line(parse::BUF_SIZE).await?;
+        let size = size_line.split(|&b| b == b';').next().unwrap_or_default().trim_ascii();
+        if !size.iter().all(u8::is_ascii_hexdigit) {
+            return Err(Status::BadRequest)
+        }
+        let size = std::str::from_utf8(size).ok()
+            .and_then(|size| usize::from_str_radix(size, 16).ok())
+            .ok_or(Status::BadRequest)?;
+
+        if size == 0 {break}
+        if body.len().checked_add(size).is_none_or(|n| n >= PAYLOAD_LIMIT) {
+            return Err(Status::PayloadTooLarge)
+        }
+
+        body.extend_from_slice(chunked.exact(size).await?);
+        if chunked.exact(2).await? != b"\r\n" {
+            return Err(Status::BadRequest)
+        }
+    }
+
+    let mut section = Vec::new();
+    loop {
+        let line = chunked.line(parse::BUF_SIZE).await?;
+        if line.is_empty() {break}
+        if section.len() + line.len() > parse::BUF_SIZE {
+            return Err(Status::RequestHeaderFieldsTooLarge)
+        }
+        section.extend_from_slice(line);
+        section.extend_from_slice(b"\r\n");
+    }
+
+    if !body.is_empty() {
+        parse::body_own(&mut req, body);
+    }
+    if !section.is_empty() {
+        parse::trailers(&mut req, section)?;
+    }
+
+    Ok(())
+}
+
 
 
 
@@ -185,6 +311,19 @@ async fn test_load_request() {
             .with(Host, "http://127.0.0.1:3000")
             .with_body("application/json", "{\"name\":\"whttp\",\"age\":")
         );
+        assert_eq!(parse::rest_bytes(&req), b"0}");
+    }
+    {
+        let mut case: &[u8] = b"\
+            GET /chat HTTP/1.1\r\n\
+            Connection: Upgrade\r\n\
+            Upgrade: my-protocol\r\n\
+            \r\n\
+            HELLO\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Ok(Some(())));
+        assert_eq!(req.body(), None);
+        assert_eq!(parse::rest_bytes(&req), b"HELLO");
     }
     {
         let mut case: &[u8] = b"\
@@ -218,4 +357,109 @@ async fn test_load_request() {
             .with_body("application/json", "{\"name\":\"whttp\",\"age\":0}")
         );
     }
+    {
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Host: http://127.0.0.1:3000\r\n\
+            Transfer-Encoding: chunked\r\n\
+            Trailer: Server-Timing\r\n\
+            \r\n\
+            7\r\n\
+            Mozilla\r\n\
+            11;ext=val\r\n\
+            Developer Network\r\n\
+            0\r\n\
+            Server-Timing: total;dur=123.4\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Ok(Some(())));
+        assert_eq!(req.body(), Some(&b"MozillaDeveloper Network"[..]));
+        assert_eq!(req.trailer(&crate::Header::def("Server-Timing")), Some("total;dur=123.4"));
+    }
+    {
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            0\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Ok(Some(())));
+        assert_eq!(req.body(), None);
+        assert_eq!(req.trailers(), None);
+    }
+    {
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            zz\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Err(Status::BadRequest));
+    }
+    {
+        /* `from_str_radix` alone accepts a leading `+` */
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            +7\r\n\
+            Mozilla\r\n\
+            0\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Err(Status::BadRequest));
+    }
+    {
+        /* size overflowing with the body so far */
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            7\r\n\
+            Mozilla\r\n\
+            ffffffffffffffff\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Err(Status::PayloadTooLarge));
+    }
+    {
+        let mut case: &[u8] = b"\
+            POST /api/upload HTTP/1.1\r\n\
+            Transfer-Encoding: gzip\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Err(Status::NotImplemented));
+    }
+    {
+        let mut case: &[u8] = b"\
+            CONNECT server.example.com:443 HTTP/1.1\r\n\
+            Host: server.example.com:443\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Ok(Some(())));
+        assert_eq!(*req, Request::CONNECT("server.example.com:443").with(Host, "server.example.com:443"));
+        assert_eq!(req.authority(), Some("server.example.com:443"));
+    }
+    {
+        let mut case: &[u8] = b"\
+            CONNECT [2001:db8::1]:8443 HTTP/1.1\r\n\
+            \r\n\
+        ";
+        assert_eq!(load(req.as_mut(), &mut case).await, Ok(Some(())));
+        assert_eq!(req.authority(), Some("[2001:db8::1]:8443"));
+    }
+    for case in [
+        &b"CONNECT server.example.com HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT :443 HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT /index.html HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT server.example.com:https HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT server.example.com:0 HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT server.example.com:99999 HTTP/1.1\r\n\r\n"[..],
+        &b"CONNECT [2001:db8::1]:65536 HTTP/1.1\r\n\r\n"[..],
+    ] {
+        let mut case = case;
+        assert_eq!(load(req.as_mut(), &mut case).await, Err(Status::BadRequest));
+    }
 }
@@ -2,4 +2,7 @@ mod load;
 pub use load::{init, load};
 
 mod send;
-pub use send::{send, Upgrade};
+pub use send::{send, send_with, send_interim, send_interim_with, Options, Upgrade};
+
+mod upgrade;
+pub use upgrade::{RawUpgrade, Upgraded};
@@ -1,9 +1,12 @@
-use crate::{Response, Status, response::Body, io::Write};
-use crate::header::{ContentLength, SetCookie};
+use super::RawUpgrade;
+use crate::{Header, Headers, Response, Status, response::Body, io::Write};
+use crate::header::{ContentLength, SetCookie, Trailer, TransferEncoding};
 
 pub enum Upgrade {
     None,
 
+    Raw(RawUpgrade),
+
     #[cfg(feature="ws")]
     WebSocket(mews::WebSocket)
 }
@@ -13,6 +16,11 @@ const _: () = {
             match self {
                 Self::None => f.write_str("{no upgrade}"),
 
+                Self::Raw(raw) => match raw.protocol() {
+                    Some(protocol) => write!(f, "{{upgrade to {protocol}}}"),
+                    None => f.write_str("{upgrade to raw connection}"),
+                },
+
                 #[cfg(feature="ws")]
                 Self::WebSocket(_) => f.write_str("{upgrade to WebSocket}"),
             }
@@ -23,43 +31,157 @@ const _: () = {
             match (self, other) {
                 (Upgrade::None, Upgrade::None) => true,
 
+                (Upgrade::Raw(a), Upgrade::Raw(b)) => a.protocol() == b.protocol(),
+
                 #[cfg(feature="ws")]
                 (Upgrade::WebSocket(_), Upgrade::WebSocket(_)) => true,
 
-                #[cfg(feature="ws")]
                 _ => false
             }
         }
     }
 };
 
-#[cfg_attr(not(feature="sse"), inline)]
-pub async fn send(
+/// Options of how `send_with` and `send_interim_with` write the response head.
+/// 
+/// ## example
+/// ```no_run
+/// # async fn __(conn: &mut tokio::net::TcpStream, res: whttp::Response) -> std::io::Result<()> {
+/// use whttp::http1;
+/// 
+/// const OPTIONS: http1::Options = http1::Options::new()
+///     .title_case()
+///     .sorted();
+/// 
+/// http1::send_with(res, conn, OPTIONS).await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Options {
+    title_case: bool,
+    sorted:     bool,
+    trailers:   bool,
+}
+impl Options {
+    /// write header names as stored, preserving original casing of parsed headers,
+    /// in the order of `Headers::iter`, and drop trailers
+    pub const fn new() -> Self {
+        Self { title_case: false, sorted: false, trailers: false }
+    }
+
+    /// write header names in canonical casing ( see `Header::canonical` )
+    pub const fn title_case(mut self) -> Self {
+        self.title_case = true;
+        self
+    }
+
+    /// write headers sorted by case-insensitive name
+    pub const fn sorted(mut self) -> Self {
+        self.sorted = true;
+        self
+    }
+
+    /// send trailers when `accepted`, typically `Request::accepts_trailers`
+    /// ( `TE: trailers` ) of the request being responded
+    pub const fn trailers(mut self, accepted: bool) -> Self {
+        self.trailers = accepted;
+        self
+    }
+}
+impl Default for Options {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Send an interim `1xx` response, like `100 Continue` or `103 Early Hints`,
+/// before the final response. This can be called any number of times before `send`.
+/// 
+/// Fails with `InvalidInput`, writing nothing, when `res` isn't `1xx` (`101 Switching Protocols`
+/// is a final response in HTTP/1.1 and must be sent by `send`) or has a body,
+/// `Content-Length` or `Transfer-Encoding`.
+/// 
+/// ## example
+/// ```no_run
+/// # async fn __(conn: &mut tokio::net::TcpStream) -> std::io::Result<()> {
+/// use whttp::{Response, http1};
+/// use whttp::header::Link;
+/// 
+/// http1::send_interim(
+///     Response::EarlyHints()
+///         .with(Link, "</style.css>; rel=preload; as=style")
+///         .with(Link, "</script.js>; rel=preload; as=script"),
+///     conn
+/// ).await?;
+/// 
+/// http1::send(Response::OK().with_html("<!DOCTYPE html>..."), conn).await?;
+/// # Ok(()) }
+/// ```
+#[inline]
+pub async fn send_interim(
+    res: Response,
+    conn: &mut (impl Write + Unpin)
+) -> Result<(), std::io::Error> {
+    send_interim_with(res, conn, Options::new()).await
+}
+
+/// `send_interim` with `Options`
+pub async fn send_interim_with(
     mut res: Response,
+    conn: &mut (impl Write + Unpin),
+    options: Options,
+) -> Result<(), std::io::Error> {
+    if !res.status().is_informational() || res.status() == Status::SwitchingProtocols {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+            format!("`{}` is not an interim response status", res.status())
+        ))
+    }
+    if res.body().is_some()
+    || res.header(ContentLength).is_some()
+    || res.header(TransferEncoding).is_some() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput,
+            "interim response can't have body, `Content-Length` or `Transfer-Encoding`"
+        ))
+    }
+
+    conn.write_all(&head(&mut res, options)).await?;
+    conn.flush().await
+}
+
+#[inline]
+pub async fn send(
+    res: Response,
     conn: &mut (impl Write + Unpin)
+) -> Result<Upgrade, std::io::Error> {
+    send_with(res, conn, Options::new()).await
+}
+
+/// `send` with `Options`
+#[cfg_attr(not(feature="sse"), inline)]
+pub async fn send_with(
+    mut res: Response,
+    conn: &mut (impl Write + Unpin),
+    options: Options,
 ) -> Result<Upgrade, std::io::Error> {
     if res.header(ContentLength).is_none()
     && res.body().is_none()
-    && res.status() != Status::NoContent {
+    && res.status() != Status::NoContent
+    && !res.status().is_informational() {
         res.set(ContentLength, "0");
     }
 
-    let mut buf = [
-        b"HTTP/1.1 ", res.status().message().as_bytes(), b"\r\n"
-    ].concat();
-    if let Some(set_cookie) = res.take(SetCookie) {
-        for set_cookie in set_cookie.split(',') {
-            buf.extend_from_slice(b"Set-Cookie: ");
-            buf.extend_from_slice(set_cookie.as_bytes());
-            buf.push(b'\r'); buf.push(b'\n');
+    let trailers = res.take_trailers().filter(|t|
+        options.trailers && !t.is_empty() && is_chunkable(res.body())
+    );
+    if let Some(trailers) = &trailers {
+        if res.header(Trailer).is_none() {
+            res.set(Trailer, trailers.iter().map(|(h, _)| &**h).collect::<Vec<_>>().join(", "));
         }
+        res.set(ContentLength, None)
+            .set(TransferEncoding, "chunked");
     }
-    for (h, v) in res.headers().iter() {
-        buf.extend_from_slice(h.as_bytes());
-        buf.push(b':'); buf.push(b' ');
-        buf.extend_from_slice(v.as_bytes());
-        buf.push(b'\r'); buf.push(b'\n');
-    }; buf.push(b'\r'); buf.push(b'\n');
+
+    let mut buf = head(&mut res, options);
 
     match res.take_body() {
         None => {
@@ -70,7 +192,13 @@ pub async fn send(
         }
 
         Some(Body::Payload(payload)) => {
-            buf.extend_from_slice(&payload);
+            match &trailers {
+                None => buf.extend_from_slice(&payload),
+                Some(trailers) => {
+                    push_chunk(&mut buf, &payload);
+                    push_last_chunk(&mut buf, Some(trailers), options);
+                }
+            }
 
             conn.write_all(&buf).await?;
             conn.flush().await?;
@@ -94,24 +222,33 @@ pub async fn send(
                     message.push(b'\n');
                 }; message.push(b'\n');
 
-                let size_hex = hexized_bytes(message.len());
-                let size_hex = &size_hex[size_hex.iter().position(|&b| b != b'0').unwrap_or(0)..];
-
                 let mut chunk = Vec::with_capacity(
-                    size_hex.len() + "\r\n".len() + message.len() + "\r\n".len()
+                    size_of::<usize>() * 2 + "\r\n".len() + message.len() + "\r\n".len()
                 );
-                chunk.extend_from_slice(size_hex);
-                chunk.push(b'\r'); chunk.push(b'\n');
-                chunk.extend_from_slice(&message);
-                chunk.push(b'\r'); chunk.push(b'\n');
+                push_chunk(&mut chunk, &message);
 
                 conn.write_all(&chunk).await?;
                 conn.flush().await?;
             }
 
+            let mut last_chunk = Vec::with_capacity("0\r\n\r\n".len());
+            push_last_chunk(&mut last_chunk, trailers.as_ref(), options);
+
+            conn.write_all(&last_chunk).await?;
+            conn.flush().await?;
+
             Ok(Upgrade::None)
         }
 
+        Some(Body::Upgrade) => {
+            conn.write_all(&buf).await?;
+            conn.flush().await?;
+
+            Ok(Upgrade::Raw(RawUpgrade {
+                protocol: res.take(crate::header::Upgrade)
+            }))
+        }
+
         #[cfg(feature="ws")]
         Some(Body::WebSocket(ws)) => {
             conn.write_all(&buf).await?;
@@ -122,7 +259,89 @@ pub async fn send(
     }
 }
 
-#[cfg(feature="sse")]
+/// status line and headers, terminated by an empty line
+#[inline]
+fn head(res: &mut Response, options: Options) -> Vec<u8> {
+    let mut buf = [
+        b"HTTP/1.1 ", res.status().message().as_bytes(), b"\r\n"
+    ].concat();
+    if let Some(set_cookie) = res.take(SetCookie) {
+        for set_cookie in set_cookie.split(',') {
+            buf.extend_from_slice(b"Set-Cookie: ");
+            buf.extend_from_slice(set_cookie.as_bytes());
+            buf.push(b'\r'); buf.push(b'\n');
+        }
+    }
+    if options.sorted {
+        let mut headers = res.headers().iter().collect::<Vec<_>>();
+        headers.sort_by(|(a, _), (b, _)| Iterator::cmp(
+            a.bytes().map(|b| b.to_ascii_lowercase()),
+            b.bytes().map(|b| b.to_ascii_lowercase())
+        ));
+        push_fields(&mut buf, headers.into_iter(), options);
+    } else {
+        push_fields(&mut buf, res.headers().iter(), options);
+    }
+    buf.push(b'\r'); buf.push(b'\n');
+    buf
+}
+
+#[inline]
+fn push_fields<'h>(
+    buf: &mut Vec<u8>,
+    fields: impl Iterator<Item = (&'h Header, &'h str)>,
+    options: Options,
+) {
+    for (h, v) in fields {
+        if options.title_case {
+            buf.extend_from_slice(h.canonical().as_bytes());
+        } else {
+            buf.extend_from_slice(h.as_bytes());
+        }
+        buf.push(b':'); buf.push(b' ');
+        buf.extend_from_slice(v.as_bytes());
+        buf.push(b'\r'); buf.push(b'\n');
+    }
+}
+
+/// whether the body can be sent with chunked transfer coding
+#[inline]
+fn is_chunkable(body: Option<&Body>) -> bool {
+    match body {
+        Some(Body::Payload(_)) => true,
+
+        #[cfg(feature="sse")]
+        Some(Body::Stream(_)) => true,
+
+        _ => false
+    }
+}
+
+/// push `data` as a chunk, skipping empty `data` as it would be the last chunk
+#[inline]
+fn push_chunk(buf: &mut Vec<u8>, data: &[u8]) {
+    if data.is_empty() {return}
+
+    let size_hex = hexized_bytes(data.len());
+    let size_hex = &size_hex[size_hex.iter().position(|&b| b != b'0').unwrap_or(0)..];
+
+    buf.reserve(size_hex.len() + "\r\n".len() + data.len() + "\r\n".len());
+    buf.extend_from_slice(size_hex);
+    buf.push(b'\r'); buf.push(b'\n');
+    buf.extend_from_slice(data);
+    buf.push(b'\r'); buf.push(b'\n');
+}
+
+/// push the last chunk with `trailers`, terminating the chunked body
+#[inline]
+fn push_last_chunk(buf: &mut Vec<u8>, trailers: Option<&Headers>, options: Options) {
+    buf.push(b'0'); buf.push(b'\r'); buf.push(b'\n');
+    if let Some(trailers) = trailers {
+        push_fields(buf, trailers.iter(), options);
+    }
+    buf.push(b'\r'); buf.push(b'\n');
+}
+
 #[inline]
 fn hexized_bytes(n: usize) -> [u8; size_of::<usize>() * 2] {
     unsafe {// SAFETY: mapping u8 -> u8 u8
@@ -155,7 +374,6 @@ fn next<S: futures_core::Stream>(stream: &mut S) -> impl std::future::Future<Out
 
 
 
-#[cfg(feature="sse")]
 #[cfg(test)]
 #[test]
 fn test_hexized_bytes() {
@@ -221,6 +439,7 @@ async fn test_send_response() {
         assert_eq!(send(Response::OK(), &mut buf).await.unwrap(), Upgrade::None);
         assert_bytes!(buf, b"\
             HTTP/1.1 200 OK\r\n\
+            Content-Length: 0\r\n\
             \r\n\
         ");
     }
@@ -232,8 +451,8 @@ async fn test_send_response() {
         &mut buf).await.unwrap(), Upgrade::None);
         assert_bytes!(buf, b"\
             HTTP/1.1 200 OK\r\n\
-            Content-Length: 13\r\n\
             Content-Type: text/plain; charset=UTF-8\r\n\
+            Content-Length: 13\r\n\
             \r\n\
             Hello, world!\
         ");
@@ -247,9 +466,9 @@ async fn test_send_response() {
         &mut buf).await.unwrap(), Upgrade::None);
         assert_bytes!(buf, b"\
             HTTP/1.1 404 Not Found\r\n\
-            Content-Length: 64\r\n\
-            Content-Type: text/html; charset=UTF-8\r\n\
             Origin: https://ohkami.rs\r\n\
+            Content-Type: text/html; charset=UTF-8\r\n\
+            Content-Length: 64\r\n\
             \r\n\
             <h1>Not Found</h1><p>no resource was found for your request.</p>\
         ");
@@ -263,9 +482,9 @@ async fn test_send_response() {
         &mut buf).await.unwrap(), Upgrade::None);
         assert_bytes!(buf, b"\
             HTTP/1.1 404 Not Found\r\n\
-            Content-Length: 64\r\n\
-            Content-Type: text/html; charset=UTF-8\r\n\
             Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+            Content-Type: text/html; charset=UTF-8\r\n\
+            Content-Length: 64\r\n\
             \r\n\
             <h1>Not Found</h1><p>no resource was found for your request.</p>\
         ");
@@ -281,10 +500,10 @@ async fn test_send_response() {
         &mut buf).await.unwrap(), Upgrade::None);
         assert_bytes!(buf, b"\
             HTTP/1.1 200 OK\r\n\
+            Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
+            Content-Type: text/event-stream\r\n\
             Cache-Control: no-cache, must-revalidate\r\n\
             Transfer-Encoding: chunked\r\n\
-            Content-Type: text/event-stream\r\n\
-            Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
             \r\n\
             e\r\n\
             data: Hello!\n\n\r\n\
@@ -292,6 +511,8 @@ async fn test_send_response() {
             data: Hello!\n\n\r\n\
             e\r\n\
             data: Hello!\n\n\r\n\
+            0\r\n\
+            \r\n\
         ");
     }
     #[cfg(feature="ws")] {
@@ -305,11 +526,179 @@ async fn test_send_response() {
         &mut buf).await.unwrap(), Upgrade::WebSocket(websocket().1));
         assert_bytes!(buf, format!("\
             HTTP/1.1 101 Switching Protocols\r\n\
+            Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
             Connection: Upgrade\r\n\
             Upgrade: websocket\r\n\
             Sec-WebSocket-Accept: {sign:}\r\n\
-            Date: Sun, 06 Nov 1994 08:49:37 GMT\r\n\
             \r\n\
         ", sign=(websocket().0)).as_bytes());
     }
 }
+
+#[cfg(all(feature="DEBUG",feature="rt_tokio"))]
+#[cfg(test)]
+#[tokio::test]
+async fn test_send_interim() {
+    use crate::header::*;
+
+    {
+        let mut buf = Vec::<u8>::new();
+        send_interim(Response::Continue(), &mut buf).await.unwrap();
+        assert_eq!(buf, b"HTTP/1.1 100 Continue\r\n\r\n");
+    }
+    {
+        let mut buf = Vec::<u8>::new();
+        send_interim(
+            Response::EarlyHints()
+            .with(Link, "</style.css>; rel=preload; as=style"),
+        &mut buf).await.unwrap();
+        send(Response::NoContent(), &mut buf).await.unwrap();
+        assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+            HTTP/1.1 103 Early Hints\r\n\
+            Link: </style.css>; rel=preload; as=style\r\n\
+            \r\n\
+            HTTP/1.1 204 No Content\r\n\
+            \r\n\
+        ");
+    }
+    {
+        let mut buf = Vec::<u8>::new();
+        assert!(send_interim(Response::OK(), &mut buf).await.is_err());
+        assert!(send_interim(Response::SwitchingProtocols(), &mut buf).await.is_err());
+        assert!(send_interim(Response::EarlyHints().with_text("Hi"), &mut buf).await.is_err());
+        assert!(send_interim(Response::Continue().with(ContentLength, "0"), &mut buf).await.is_err());
+        assert!(buf.is_empty());
+    }
+}
+
+#[cfg(all(feature="DEBUG",feature="rt_tokio"))]
+#[cfg(test)]
+#[tokio::test]
+async fn test_send_trailers() {
+    use crate::Header;
+
+    const SERVER_TIMING: &Header = &Header::def("Server-Timing");
+
+    {
+        let mut buf = Vec::<u8>::new();
+        send_with(
+            Response::OK()
+            .with_text("Hello, world!")
+            .with_trailer(SERVER_TIMING, "total;dur=123.4"),
+        &mut buf, Options::new().trailers(true)).await.unwrap();
+
+        assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+            HTTP/1.1 200 OK\r\n\
+            Content-Type: text/plain; charset=UTF-8\r\n\
+            Trailer: Server-Timing\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            d\r\n\
+            Hello, world!\r\n\
+            0\r\n\
+            Server-Timing: total;dur=123.4\r\n\
+            \r\n\
+        ");
+    }
+    {
+        let mut buf = Vec::<u8>::new();
+        send_with(
+            Response::NoContent()
+            .with_trailer(SERVER_TIMING, "total;dur=123.4"),
+        &mut buf, Options::new().trailers(true)).await.unwrap();
+        assert_eq!(buf, b"HTTP/1.1 204 No Content\r\n\r\n");
+    }
+    {
+        /* without `TE: trailers` */
+        let req = crate::Request::GET("/");
+        let mut buf = Vec::<u8>::new();
+        send_with(
+            Response::OK()
+            .with_text("Hello, world!")
+            .with_trailer(SERVER_TIMING, "total;dur=123.4"),
+        &mut buf, Options::new().trailers(req.accepts_trailers())).await.unwrap();
+
+        assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+            HTTP/1.1 200 OK\r\n\
+            Content-Type: text/plain; charset=UTF-8\r\n\
+            Content-Length: 13\r\n\
+            \r\n\
+            Hello, world!\
+        ");
+    }
+}
+
+#[cfg(all(feature="DEBUG",feature="rt_tokio"))]
+#[cfg(test)]
+#[tokio::test]
+async fn test_send_with_options() {
+    use crate::Header;
+
+    const ETAG_LOWER:  &Header = &Header::def("etag");
+    const X_CUSTOM:    &Header = &Header::def("x-custom-DATA");
+    const VARY_UPPER:  &Header = &Header::def("VARY");
+
+    let res = || Response::NoContent()
+        .with(X_CUSTOM, "custom")
+        .with(ETAG_LOWER, "\"xyzzy\"")
+        .with(VARY_UPPER, "Origin");
+
+    {
+        let mut buf = Vec::<u8>::new();
+        send_with(res(), &mut buf, Options::new().sorted()).await.unwrap();
+        assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+            HTTP/1.1 204 No Content\r\n\
+            etag: \"xyzzy\"\r\n\
+            VARY: Origin\r\n\
+            x-custom-DATA: custom\r\n\
+            \r\n\
+        ");
+    }
+    {
+        let mut buf = Vec::<u8>::new();
+        send_with(res(), &mut buf, Options::new().sorted().title_case()).await.unwrap();
+        assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+            HTTP/1.1 204 No Content\r\n\
+            ETag: \"xyzzy\"\r\n\
+            Vary: Origin\r\n\
+            X-Custom-Data: custom\r\n\
+            \r\n\
+        ");
+    }
+}
+
+#[cfg(all(feature="DEBUG",feature="rt_tokio"))]
+#[cfg(test)]
+#[tokio::test]
+async fn test_send_upgrade() {
+    let mut buf = Vec::<u8>::new();
+    let upgrade = send(
+        Response::SwitchingProtocols()
+        .with_upgrade("my-protocol"),
+    &mut buf).await.unwrap();
+
+    let Upgrade::Raw(raw) = upgrade else {panic!("expected `Upgrade::Raw`")};
+    assert_eq!(raw.protocol(), Some("my-protocol"));
+
+    assert_eq!(std::str::from_utf8(&buf).unwrap(), "\
+        HTTP/1.1 101 Switching Protocols\r\n\
+        Connection: Upgrade\r\n\
+        Upgrade: my-protocol\r\n\
+        \r\n\
+    ");
+}
+
+#[cfg(all(feature="DEBUG",feature="rt_tokio"))]
+#[cfg(test)]
+#[tokio::test]
+async fn test_send_tunnel() {
+    let mut buf = Vec::<u8>::new();
+    let upgrade = send(
+        Response::OK()
+        .with_text("this is dropped")
+        .with_tunnel(),
+    &mut buf).await.unwrap();
+
+    assert_eq!(upgrade, Upgrade::Raw(RawUpgrade { protocol: None }));
+    assert_eq!(buf, b"HTTP/1.1 200 OK\r\n\r\n");
+}
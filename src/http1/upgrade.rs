@@ -0,0 +1,80 @@
+use crate::{Request, Value, request::parse};
+
+/// Handle of a connection switched to another protocol by `Response::set_upgrade`,
+/// or to a tunnel by `Response::set_tunnel`, returned from `send` as `Upgrade::Raw`.
+/// 
+/// ## example
+/// ```no_run
+/// use whttp::{Request, Response, http1};
+/// use whttp::header::Upgrade;
+/// 
+/// # async fn __(listener: tokio::net::TcpListener) -> std::io::Result<()> {
+/// let (mut conn, _) = listener.accept().await?;
+/// 
+/// let mut req = http1::init();
+/// let mut req = std::pin::Pin::new(&mut req);
+/// 
+/// while let Ok(Some(())) = http1::load(req.as_mut(), &mut conn).await {
+///     let res = if req.header(Upgrade) == Some("my-protocol") {
+///         Response::SwitchingProtocols().with_upgrade("my-protocol")
+///     } else {
+///         Response::UpgradeRequired()
+///     };
+/// 
+///     match http1::send(res, &mut conn).await? {
+///         http1::Upgrade::Raw(raw) => {
+///             let (conn, buffered) = raw.attach(&req, conn).into_parts();
+///             /* speak `my-protocol` on `conn`, starting from `buffered` bytes */
+///             return Ok(())
+///         }
+///         _ => continue
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+pub struct RawUpgrade {
+    pub(super) protocol: Option<Value>,
+}
+
+impl RawUpgrade {
+    /// the protocol in `Upgrade` header of the response, `None` for a tunnel
+    #[inline]
+    pub fn protocol(&self) -> Option<&str> {
+        self.protocol.as_deref()
+    }
+
+    /// Take over `conn` together with the bytes `load` has already read
+    /// from it beyond `req`.
+    pub fn attach<C>(self, req: &Request, conn: C) -> Upgraded<C> {
+        Upgraded {
+            conn,
+            buffered: parse::rest_bytes(req).to_vec(),
+        }
+    }
+}
+
+/// Connection taken over by the application after an upgrade.
+/// 
+/// `buffered` bytes were received from `conn` before the upgrade,
+/// so they must be processed before reading from `conn`.
+pub struct Upgraded<C> {
+    conn:     C,
+    buffered: Vec<u8>,
+}
+
+impl<C> Upgraded<C> {
+    #[inline]
+    pub fn conn(&mut self) -> &mut C {
+        &mut self.conn
+    }
+
+    #[inline]
+    pub fn buffered(&self) -> &[u8] {
+        &self.buffered
+    }
+
+    #[inline]
+    pub fn into_parts(self) -> (C, Vec<u8>) {
+        (self.conn, self.buffered)
+    }
+}
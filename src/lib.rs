@@ -36,6 +36,8 @@ pub mod headers;
 pub mod request;
 pub mod response;
 
+mod scan;
+
 pub mod header {pub use crate::headers::standard::*;}
 pub use headers::{Header, Value, Headers};
 pub use request::{Method, Request};
@@ -16,6 +16,13 @@ macro_rules! Method {
                     _ => None
                 }
             }
+
+            #[inline]
+            pub const fn as_str(&self) -> &'static str {
+                match self {
+                    $( Self::$name => stringify!($name), )*
+                }
+            }
         }
 
         impl super::Request {$(
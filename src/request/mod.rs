@@ -5,7 +5,7 @@ pub use method::Method;
 use memory::Memory;
 
 use crate::headers::{Header, Headers, SetHeader, Value};
-use crate::util::{Bytes, IntoBytes, IntoStr, Str};
+use crate::util::{Bytes, IntoBytes, IntoStr, Str, mime};
 use ::std::borrow::Cow;
 use ::unsaferef::UnsafeRef;
 use ::percent_encoding::{percent_decode, percent_encode, NON_ALPHANUMERIC};
@@ -18,6 +18,15 @@ pub struct Request {
     query:   Option<Str>,
     headers: Headers,
     body:    Option<Bytes>,
+    trailers: Option<Box<Trailers>>,
+    /// range of `__buf__` read from connection but not consumed as this request
+    rest: (u16, u16),
+}
+
+/// trailer fields with the bytes they refer to
+struct Trailers {
+    __buf__: Vec<u8>,
+    headers: Headers,
 }
 
 impl Request {
@@ -50,6 +59,15 @@ impl Request {
         }
     }
 
+    /// `host:port` of a `CONNECT` request, stored as its path
+    #[inline]
+    pub fn authority(&self) -> Option<&str> {
+        match self.method {
+            Method::CONNECT => Some(&self.path),
+            _ => None
+        }
+    }
+
     #[inline]
     pub fn query(&self) -> Option<&str> {
         match &self.query {
@@ -70,6 +88,48 @@ impl Request {
             .map(crate::util::cookie::parse)
     }
 
+    /// parsed `Authorization`, `None` if missing or invalid
+    #[inline]
+    pub fn authorization(&self) -> Option<crate::util::auth::Credentials<'_>> {
+        self.header(crate::header::Authorization)
+            .and_then(crate::util::auth::Credentials::parse)
+    }
+
+    /// parsed `Proxy-Authorization`, `None` if missing or invalid
+    #[inline]
+    pub fn proxy_authorization(&self) -> Option<crate::util::auth::Credentials<'_>> {
+        self.header(crate::header::ProxyAuthorization)
+            .and_then(crate::util::auth::Credentials::parse)
+    }
+
+    /// content negotiation by `Accept`, `Accept-Language` and `Accept-Encoding`
+    #[inline]
+    pub fn negotiation(&self) -> crate::util::Negotiation<'_> {
+        crate::util::Negotiation::new(&self.headers)
+    }
+
+    /// trailer fields received after a chunked body, if any
+    #[inline]
+    pub fn trailers(&self) -> Option<&Headers> {
+        match &self.trailers {
+            Some(t) => Some(&t.headers),
+            None => None
+        }
+    }
+    #[inline]
+    pub fn trailer(&self, header: &Header) -> Option<&str> {
+        self.trailers()?.get(header)
+    }
+
+    /// whether the client advertised `TE: trailers`, meaning it's willing to
+    /// accept trailer fields in the response
+    pub fn accepts_trailers(&self) -> bool {
+        self.header(crate::header::TE).is_some_and(|te| te
+            .split(',')
+            .any(|t| t.trim().eq_ignore_ascii_case("trailers"))
+        )
+    }
+
     #[inline]
     pub fn body(&self) -> Option<&[u8]> {
         match &self.body {
@@ -89,7 +149,9 @@ impl Request {
             path:    path.into_str(),
             query:   None,
             headers: Headers::with_capacity(4),
-            body:    None
+            body:    None,
+            trailers: None,
+            rest:    (0, 0),
         }
     }
 
@@ -105,20 +167,20 @@ impl Request {
     }
 
     #[inline]
-    pub fn with_body(mut self, content_type: &'static str, body: impl IntoBytes) -> Self {
+    pub fn with_body(mut self, content_type: impl Into<Value>, body: impl IntoBytes) -> Self {
         self.set_body(content_type, body);
         self
     }
 
     pub fn with_text(self, text: impl Into<Cow<'static, str>>) -> Self {
-        self.with_body("text/plain; charset=UTF-8", match text.into() {
+        self.with_body(mime::TEXT_PLAIN_UTF_8, match text.into() {
             Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
             Cow::Owned(s)    => Cow::Owned(s.into_bytes())
         })
     }
 
     pub fn with_json(self, json: impl ::serde::Serialize) -> Self {
-        self.with_body("application/json",
+        self.with_body(mime::APPLICATION_JSON,
             ::serde_json::to_vec(&json).expect("failed to serialize")
         )
     }
@@ -172,11 +234,11 @@ impl Request {
     }
 
     #[inline]
-    pub fn set_body(&mut self, content_type: &'static str, body: impl IntoBytes) -> &mut Self {
+    pub fn set_body(&mut self, content_type: impl Into<Value>, body: impl IntoBytes) -> &mut Self {
         use crate::header::{ContentType, ContentLength};
 
         let body = body.into_bytes();
-        self.set(ContentType, content_type)
+        self.set(ContentType, content_type.into())
             .set(ContentLength, body.len());
         self.body = Some(body);
         self
@@ -190,6 +252,50 @@ pub mod parse {
 
     pub const BUF_SIZE: usize = 1024;
 
+    /// Cursor over a request head, finding delimiters by vectorized scanning
+    pub struct Scanner<'b> {
+        bytes:     &'b [u8],
+        pub index: usize,
+    }
+
+    impl<'b> Scanner<'b> {
+        #[inline]
+        pub const fn new(bytes: &'b [u8]) -> Self {
+            Self { bytes, index: 0 }
+        }
+
+        #[inline(always)]
+        pub fn remaining(&self) -> &'b [u8] {
+            &self.bytes[self.index..]
+        }
+
+        /// Read until `delimiter` or the end, leaving `delimiter` unread.
+        #[inline]
+        pub fn read_until(&mut self, delimiter: u8) -> &'b [u8] {
+            let remaining = self.remaining();
+            let len = crate::scan::find(remaining, delimiter).unwrap_or(remaining.len());
+            self.index += len;
+            &remaining[..len]
+        }
+
+        /// Read until `a`, `b` or the end, leaving them unread.
+        #[inline]
+        pub fn read_until_either(&mut self, a: u8, b: u8) -> &'b [u8] {
+            let remaining = self.remaining();
+            let len = crate::scan::find2(remaining, a, b).unwrap_or(remaining.len());
+            self.index += len;
+            &remaining[..len]
+        }
+
+        /// Consume `token` if the remaining bytes start with it.
+        #[inline]
+        pub fn consume(&mut self, token: &str) -> Option<()> {
+            if !self.remaining().starts_with(token.as_bytes()) {return None}
+            self.index += token.len();
+            Some(())
+        }
+    }
+
     pub fn new() -> Request {
         Request {
             __buf__: Some(Box::new([0; BUF_SIZE])),
@@ -199,6 +305,8 @@ pub mod parse {
             query:   None,
             headers: Headers::with_capacity(8),
             body:    None,
+            trailers: None,
+            rest:    (0, 0),
         }
     }
 
@@ -218,6 +326,8 @@ pub mod parse {
         this.query = None;
         this.headers.clear();
         this.body = None;
+        this.trailers = None;
+        this.rest = (0, 0);
     }
 
     pub fn buf(this: Pin<&mut Request>) -> &mut Box<[u8; BUF_SIZE]> {
@@ -267,6 +377,38 @@ pub mod parse {
         }
     }
 
+    #[inline]
+    /// Store authority-form request target ( `host:port` ) of `CONNECT` request as path.
+    /// 
+    /// ## SAFETY
+    /// 
+    /// * must be called just once for `this`, instead of `path`
+    /// 
+    /// * `bytes` must be alive as long as `path` of `this` is in use;
+    ///   especially, reading from `this.buf`
+    pub unsafe fn authority(this: &mut Pin<&mut Request>, bytes: &[u8]) -> Result<(), Status> {
+        let Some(colon) = bytes.iter().rposition(|&b| b == b':') else {
+            return Err(Status::BadRequest)
+        };
+        let (host, port) = (&bytes[..colon], &bytes[colon+1..]);
+
+        let valid_host = match host {
+            [b'[', ipv6 @ .., b']'] => !ipv6.is_empty() && ipv6.iter().all(|b| b.is_ascii_hexdigit() || matches!(b, b':' | b'.')),
+            _ => !host.is_empty() && host.iter().all(|b| b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~' | b'%')),
+        };
+        let valid_port = (1..=5).contains(&port.len()) && port.iter().all(u8::is_ascii_digit)
+            && std::str::from_utf8(port).ok().and_then(|p| p.parse::<u16>().ok()).is_some_and(|p| p != 0);
+        if !(valid_host && valid_port) {
+            return Err(Status::BadRequest)
+        }
+
+        this.path = Str::Ref(UnsafeRef::new(
+            // SAFETY: already checked `bytes` is ascii
+            std::str::from_utf8_unchecked(bytes)
+        ));
+        Ok(())
+    }
+
     #[inline]
     /// Store bytes like `query=value`, `q1=v1&q2=v2` into `this.query`.
     /// 
@@ -304,6 +446,49 @@ pub mod parse {
     pub fn body_own(this: &mut Pin<&mut Request>, bytes: Vec<u8>) {
         this.body = Some(Bytes::Own(bytes))
     }
+
+    /// Mark `this.buf[range]` as bytes read from connection beyond this request,
+    /// e.g. the first bytes of another protocol the connection is upgraded to
+    #[inline]
+    pub fn rest(this: &mut Pin<&mut Request>, range: std::ops::Range<usize>) {
+        const _: () = assert!(BUF_SIZE <= u16::MAX as usize);
+
+        if range.start < range.end && range.end <= BUF_SIZE {
+            this.rest = (range.start as u16, range.end as u16)
+        }
+    }
+
+    /// bytes marked by `parse::rest`
+    #[inline]
+    pub fn rest_bytes(this: &Request) -> &[u8] {
+        match &this.__buf__ {
+            Some(buf) => &buf[this.rest.0 as usize..this.rest.1 as usize],
+            None => &[]
+        }
+    }
+
+    /// Store the trailer section of a chunked body: lines like `name: value\r\n`,
+    /// without the terminating empty line.
+    pub fn trailers(this: &mut Pin<&mut Request>, section: Vec<u8>) -> Result<(), Status> {
+        let mut headers = Headers::new();
+        {
+            // SAFETY: `section` is moved into `this.trailers` together with `headers`
+            // and is never mutated after that, so its heap allocation outlives them
+            let section: &[u8] = unsafe {std::slice::from_raw_parts(section.as_ptr(), section.len())};
+            for line in section.split(|&b| b == b'\n') {
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                if line.is_empty() {continue}
+
+                let colon = line.iter().position(|&b| b == b':').ok_or(Status::BadRequest)?;
+                let (name, value) = (&line[..colon], line[colon+1..].trim_ascii());
+                let name  = unsafe {Header::parse_mainly_standard(name)}.map_err(|_| Status::BadRequest)?;
+                let value = unsafe {Value::parse(value)}.map_err(|_| Status::BadRequest)?;
+                headers.push(name, value);
+            }
+        }
+        this.trailers = Some(Box::new(Trailers { __buf__: section, headers }));
+        Ok(())
+    }
 }
 
 const _: () = {
@@ -313,7 +498,8 @@ const _: () = {
             self.path == other.path &&
             self.query == other.query &&
             self.headers == other.headers &&
-            self.body == other.body
+            self.body == other.body &&
+            self.trailers() == other.trailers()
         }
     }
 
@@ -325,6 +511,7 @@ const _: () = {
                 .field("<query>", &self.query())
                 .field("<headers>", &self.headers)
                 .field("<body>", &self.body().map(|bytes| bytes.escape_ascii().to_string()))
+                .field("<trailers>", &self.trailers())
                 .finish()
         }
     }
@@ -9,6 +9,10 @@ use ::mews::WebSocket;
 pub enum Body {
     Payload(Cow<'static, [u8]>),
 
+    /// no body; the connection is handed over to another protocol
+    /// (or a raw tunnel) after the response head
+    Upgrade,
+
     #[cfg(feature="sse")]
     Stream(std::pin::Pin<Box<dyn Stream<Item = String> + Send>>),
 
@@ -21,13 +25,14 @@ impl PartialEq for Body {
         match (self, other) {
             (Body::Payload(this), Body::Payload(other)) => this == other,
 
+            (Body::Upgrade, Body::Upgrade) => true,
+
             #[cfg(feature="sse")]
             (Body::Stream(_), Body::Stream(_)) => false/* can't compare */,
 
             #[cfg(feature="ws")]
             (Body::WebSocket(_), Body::WebSocket(_)) => false/* can't compare */,
 
-            #[cfg(any(feature="sse", feature="ws"))]
             _ => false
         }
     }
@@ -39,6 +44,8 @@ impl std::fmt::Debug for Body {
             Self::Payload(p) => f.debug_tuple("Payload")
                 .field(&p.escape_ascii().to_string())
                 .finish(),
+
+            Self::Upgrade => f.write_str("Upgrade"),
             
             #[cfg(feature="sse")]
             Self::Stream(_) => f.debug_tuple("Stream")
@@ -6,6 +6,7 @@ pub use body::Body;
 
 
 use crate::headers::{Header, Value, Headers, SetHeader};
+use crate::util::{mime, range::{self, Ranges, ByteRange}};
 use ::std::borrow::Cow;
 use ::serde::Serialize;
 
@@ -19,6 +20,7 @@ pub struct Response {
     status:  Status,
     headers: Headers,
     body:    Option<Body>,
+    trailers: Option<Box<Headers>>,
 }
 
 impl Response {
@@ -27,7 +29,8 @@ impl Response {
         Self {
             status,
             headers: Headers::with_capacity(4),
-            body:    None
+            body:    None,
+            trailers: None,
         }
     }
 }
@@ -53,8 +56,6 @@ impl Response {
     pub fn payload(&self) -> Option<&[u8]> {
         match self.body()? {
             Body::Payload(p) => Some(p),
-
-            #[cfg(any(feature="sse", feature="ws"))]
             _ => None
         }
     }
@@ -67,6 +68,17 @@ impl Response {
     pub fn take_body(&mut self) -> Option<Body> {
         self.body.take()
     }
+
+    /// trailer fields to be sent after the body
+    #[inline]
+    pub fn trailers(&self) -> Option<&Headers> {
+        self.trailers.as_deref()
+    }
+
+    #[inline]
+    pub fn take_trailers(&mut self) -> Option<Headers> {
+        self.trailers.take().map(|t| *t)
+    }
 }
 
 impl Response {
@@ -87,6 +99,19 @@ impl Response {
         self
     }
 
+    /// Set a trailer field, sent after the body with chunked transfer coding.
+    /// 
+    /// Trailers are dropped for responses without body, and by `http1::send`
+    /// unless enabled by `http1::Options::trailers` for a request that
+    /// `accepts_trailers`. Clients may still discard them, so set only
+    /// fields safe to be ignored.
+    #[inline]
+    pub fn set_trailer(&mut self, header: &Header, value: impl SetHeader) -> &mut Self {
+        self.trailers.get_or_insert_with(|| Box::new(Headers::new()))
+            .set(header, value);
+        self
+    }
+
     /// drop `body`, `Content-Type` and `Content-Length` if exists
     pub fn drop_body(&mut self) -> &mut Self {
         use crate::header::{ContentLength, ContentType};
@@ -107,22 +132,77 @@ impl Response {
     #[inline]
     pub fn set_payload(
         &mut self,
-        content_type: &'static str,
+        content_type: impl Into<Value>,
         payload: impl Into<Cow<'static, [u8]>>
     ) -> &mut Self {
         use crate::header::{ContentLength, ContentType};
 
         let payload: Cow<'static, [u8]> = payload.into();
-        self.set(ContentType, content_type)
+        self.set(ContentType, content_type.into())
             .set(ContentLength, payload.len());
         self.body = Some(Body::Payload(payload));
         self
     }
 
+    /// Set `payload`, or its parts requested by `ranges`, as `200 OK`,
+    /// `206 Partial Content` ( a single part or `multipart/byteranges` ) or
+    /// `416 Range Not Satisfiable`, with `Accept-Ranges: bytes`.
+    /// 
+    /// `ranges` are typically by `util::range::Ranges::of`, which evaluates `If-Range`.
+    pub fn set_ranged_payload(
+        &mut self,
+        ranges: Option<Ranges>,
+        content_type: impl Into<Value>,
+        payload: impl Into<Cow<'static, [u8]>>
+    ) -> &mut Self {
+        let payload: Cow<'static, [u8]> = payload.into();
+        let complete_length = payload.len() as u64;
+
+        let mut payload = Some(payload);
+        let read = |range: Option<ByteRange>| Ok::<_, std::convert::Infallible>(match (range, &payload) {
+            (None, _) => payload.take().unwrap(),
+            (Some(r), Some(Cow::Borrowed(p))) => Cow::Borrowed(&p[r.first as usize..=r.last as usize]),
+            (Some(r), Some(Cow::Owned(p))) => Cow::Owned(p[r.first as usize..=r.last as usize].to_vec()),
+            (Some(_), None) => unreachable!()
+        });
+        match range::respond(self, ranges, content_type.into(), complete_length, read) {
+            Ok(()) => self,
+            Err(infallible) => match infallible {}
+        }
+    }
+
+    /// `set_ranged_payload` with a seekable source, reading only the requested parts.
+    pub fn set_ranged_reader(
+        &mut self,
+        ranges: Option<Ranges>,
+        content_type: impl Into<Value>,
+        mut reader: impl std::io::Read + std::io::Seek
+    ) -> std::io::Result<&mut Self> {
+        use std::io::SeekFrom;
+
+        let complete_length = reader.seek(SeekFrom::End(0))?;
+        range::respond(self, ranges, content_type.into(), complete_length, |range| {
+            let mut buf = Vec::new();
+            match range {
+                None => {
+                    reader.seek(SeekFrom::Start(0))?;
+                    reader.read_to_end(&mut buf)?;
+                }
+                Some(r) => {
+                    reader.seek(SeekFrom::Start(r.first))?;
+                    buf.resize(r.length() as usize, 0);
+                    reader.read_exact(&mut buf)?;
+                }
+            }
+            Ok::<_, std::io::Error>(Cow::Owned(buf))
+        })?;
+        Ok(self)
+    }
+
     #[inline]
     pub fn set_text(&mut self, text: impl Into<Cow<'static, str>>) -> &mut Self {
         let text: Cow<'static, str> = text.into();
-        self.set_payload("text/plain; charset=UTF-8", match text {
+        self.set_payload(mime::TEXT_PLAIN_UTF_8, match text {
             Cow::Borrowed(b) => Cow::Borrowed(b.as_bytes()),
             Cow::Owned(o) => Cow::Owned(o.into_bytes())
         })
@@ -131,7 +211,7 @@ impl Response {
     #[inline]
     pub fn set_html(&mut self, html: impl Into<Cow<'static, str>>) -> &mut Self {
         let text: Cow<'static, str> = html.into();
-        self.set_payload("text/html; charset=UTF-8", match text {
+        self.set_payload(mime::TEXT_HTML_UTF_8, match text {
             Cow::Borrowed(b) => Cow::Borrowed(b.as_bytes()),
             Cow::Owned(o) => Cow::Owned(o.into_bytes())
         })
@@ -140,7 +220,7 @@ impl Response {
     #[inline]
     pub fn set_json(&mut self, json: impl Serialize) -> &mut Self {
         let json: Vec<u8> = ::serde_json::to_vec(&json).expect("failed to serialize");
-        self.set_payload("application/json", json)
+        self.set_payload(mime::APPLICATION_JSON, json)
     }
 
     #[cfg(feature="sse")]
@@ -150,13 +230,47 @@ impl Response {
     ) -> &mut Self {
         use crate::header::{ContentType, CacheControl, TransferEncoding};
 
-        self.set(ContentType, "text/event-stream")
-            .set(CacheControl, "no-cache, must-revalidate")
+        self.set(ContentType, mime::TEXT_EVENT_STREAM)
+            .set(CacheControl, crate::util::CacheControl::new().no_cache().must_revalidate())
             .set(TransferEncoding, "chunked");
         self.body = Some(Body::Stream(Box::pin(stream)));
         self
     }
 
+    /// Switch the connection to `protocol` by `101 Switching Protocols`.
+    /// 
+    /// `http1::send` returns `Upgrade::Raw` for this response, by which
+    /// the application takes over the connection.
+    pub fn set_upgrade(&mut self, protocol: impl Into<Value>) -> &mut Self {
+        use crate::header::{Connection, Upgrade};
+
+        self.status = Status::SwitchingProtocols;
+        self.set(Connection, "Upgrade")
+            .set(Upgrade, protocol.into());
+        self.body = Some(Body::Upgrade);
+        self
+    }
+
+    /// Establish a tunnel for `CONNECT` request: `http1::send` returns `Upgrade::Raw`
+    /// for this response, by which the application takes over the client connection
+    /// to relay bytes to the target.
+    /// 
+    /// The status is set to `200 OK` unless it's already `2xx`, and
+    /// `Content-Length` and `Transfer-Encoding` are removed as they're not allowed
+    /// in `2xx` response to `CONNECT`.
+    pub fn set_tunnel(&mut self) -> &mut Self {
+        use crate::header::{ContentLength, ContentType, TransferEncoding};
+
+        if !matches!(self.status.code(), 200..=299) {
+            self.status = Status::OK;
+        }
+        self.set(ContentType, None)
+            .set(ContentLength, None)
+            .set(TransferEncoding, None);
+        self.body = Some(Body::Upgrade);
+        self
+    }
+
     #[cfg(feature="ws")]
     pub fn set_websocket(
         &mut self,
@@ -186,6 +300,13 @@ impl Response {
         self
     }
 
+    /// see `set_trailer`
+    #[inline]
+    pub fn with_trailer(mut self, header: &Header, value: impl Into<Value>) -> Self {
+        self.set_trailer(header, value.into());
+        self
+    }
+
     /// without `body`, `Content-Type` and `Content-Length`
     pub fn without_body(mut self) -> Self {
         self.drop_body();
@@ -202,13 +323,24 @@ impl Response {
     #[inline]
     pub fn with_payload(
         mut self,
-        content_type: &'static str,
+        content_type: impl Into<Value>,
         payload: impl Into<Cow<'static, [u8]>>
     ) -> Self {
         self.set_payload(content_type, payload);
         self
     }
 
+    #[inline]
+    pub fn with_ranged_payload(
+        mut self,
+        ranges: Option<Ranges>,
+        content_type: impl Into<Value>,
+        payload: impl Into<Cow<'static, [u8]>>
+    ) -> Self {
+        self.set_ranged_payload(ranges, content_type, payload);
+        self
+    }
+
     #[inline]
     pub fn with_text(mut self, text: impl Into<Cow<'static, str>>) -> Self {
         self.set_text(text);
@@ -236,6 +368,18 @@ impl Response {
         self
     }
 
+    /// see `set_upgrade`
+    pub fn with_upgrade(mut self, protocol: impl Into<Value>) -> Self {
+        self.set_upgrade(protocol);
+        self
+    }
+
+    /// see `set_tunnel`
+    pub fn with_tunnel(mut self) -> Self {
+        self.set_tunnel();
+        self
+    }
+
     #[cfg(feature="ws")]
     pub fn with_websocket(
         mut self,
@@ -252,7 +396,8 @@ const _: () = {
         fn eq(&self, other: &Self) -> bool {
             self.status == other.status &&
             self.headers == other.headers &&
-            self.body == other.body
+            self.body == other.body &&
+            self.trailers == other.trailers
         }
     }
 
@@ -262,6 +407,7 @@ const _: () = {
                 .field("<status>", &self.status)
                 .field("<headers>", &self.headers)
                 .field("<body>", &self.body)
+                .field("<trailers>", &self.trailers)
                 .finish()
         }
     }
@@ -26,6 +26,11 @@ macro_rules! Status {
                     $( Self::$name => $message, )*
                 }
             }
+
+            /// `1xx`
+            pub const fn is_informational(&self) -> bool {
+                matches!(self.code(), 100..=199)
+            }
         }
     };
 }
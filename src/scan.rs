@@ -0,0 +1,202 @@
+//! Vectorized scanning of request heads
+//!
+//! x86_64 uses AVX2 when it's detected at runtime and SSE2 ( always
+//! available there ) otherwise, aarch64 uses NEON, and the others scan
+//! byte by byte. Bytes shorter than a 16-byte vector, like most fields
+//! of request lines, are scanned byte by byte without the dispatch.
+
+macro_rules! scanner {
+    ($(
+        $( #[doc = $doc:literal] )*
+        fn $name:ident($( $arg:ident ),*) {
+            scalar: |$b:ident| $scalar:expr,
+            sse2:   |$x:ident| $sse2:expr,
+            avx2:   |$y:ident| $avx2:expr,
+            neon:   |$z:ident| $neon:expr,
+        }
+    )*) => {$(
+        $( #[doc = $doc] )*
+        #[inline]
+        pub(crate) fn $name(bytes: &[u8], $( $arg: u8 ),*) -> Option<usize> {
+            if bytes.len() < 16 {
+                return $name::scalar(bytes, $( $arg ),*)
+            }
+
+            #[cfg(target_arch = "x86_64")] {
+                if bytes.len() >= 32 && std::is_x86_feature_detected!("avx2") {
+                    // SAFETY: AVX2 is detected
+                    return unsafe {$name::avx2(bytes, $( $arg ),*)}
+                }
+                // SAFETY: SSE2 is always available on x86_64
+                #[allow(clippy::needless_return)]
+                return unsafe {$name::sse2(bytes, $( $arg ),*)}
+            }
+
+            #[cfg(target_arch = "aarch64")] {
+                // SAFETY: NEON is always available on aarch64
+                #[allow(clippy::needless_return)]
+                return unsafe {$name::neon(bytes, $( $arg ),*)}
+            }
+
+            #[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))] {
+                $name::scalar(bytes, $( $arg ),*)
+            }
+        }
+
+        mod $name {
+            #[inline(always)]
+            pub(super) fn scalar(bytes: &[u8], $( $arg: u8 ),*) -> Option<usize> {
+                bytes.iter().position(|&$b| $scalar)
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            #[target_feature(enable = "sse2")]
+            pub(super) unsafe fn sse2(bytes: &[u8], $( $arg: u8 ),*) -> Option<usize> {
+                use std::arch::x86_64::*;
+
+                let mut i = 0;
+                while i + 16 <= bytes.len() {
+                    let $x = _mm_loadu_si128(bytes.as_ptr().add(i).cast());
+                    let mask = _mm_movemask_epi8($sse2) as u32;
+                    if mask != 0 {return Some(i + mask.trailing_zeros() as usize)}
+                    i += 16;
+                }
+                scalar(&bytes[i..], $( $arg ),*).map(|j| i + j)
+            }
+
+            #[cfg(target_arch = "x86_64")]
+            #[target_feature(enable = "avx2")]
+            pub(super) unsafe fn avx2(bytes: &[u8], $( $arg: u8 ),*) -> Option<usize> {
+                use std::arch::x86_64::*;
+
+                let mut i = 0;
+                while i + 32 <= bytes.len() {
+                    let $y = _mm256_loadu_si256(bytes.as_ptr().add(i).cast());
+                    let mask = _mm256_movemask_epi8($avx2) as u32;
+                    if mask != 0 {return Some(i + mask.trailing_zeros() as usize)}
+                    i += 32;
+                }
+                sse2(&bytes[i..], $( $arg ),*).map(|j| i + j)
+            }
+
+            #[cfg(target_arch = "aarch64")]
+            #[target_feature(enable = "neon")]
+            pub(super) unsafe fn neon(bytes: &[u8], $( $arg: u8 ),*) -> Option<usize> {
+                use std::arch::aarch64::*;
+
+                let mut i = 0;
+                while i + 16 <= bytes.len() {
+                    let $z = vld1q_u8(bytes.as_ptr().add(i));
+                    let matched: uint8x16_t = $neon;
+                    if vmaxvq_u8(matched) != 0 {
+                        /* 4 bits for each byte, as NEON has no movemask */
+                        let nibbles = vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(vreinterpretq_u16_u8(matched))));
+                        return Some(i + (nibbles.trailing_zeros() / 4) as usize)
+                    }
+                    i += 16;
+                }
+                scalar(&bytes[i..], $( $arg ),*).map(|j| i + j)
+            }
+        }
+    )*};
+}
+
+scanner! {
+    /// position of the first `delimiter` in `bytes`
+    fn find(delimiter) {
+        scalar: |b| b == delimiter,
+        sse2:   |x| _mm_cmpeq_epi8(x, _mm_set1_epi8(delimiter as i8)),
+        avx2:   |y| _mm256_cmpeq_epi8(y, _mm256_set1_epi8(delimiter as i8)),
+        neon:   |z| vceqq_u8(z, vdupq_n_u8(delimiter)),
+    }
+
+    /// position of the first `a` or `b` in `bytes`
+    fn find2(a, b) {
+        scalar: |byte| byte == a || byte == b,
+        sse2:   |x| _mm_or_si128(
+            _mm_cmpeq_epi8(x, _mm_set1_epi8(a as i8)),
+            _mm_cmpeq_epi8(x, _mm_set1_epi8(b as i8)),
+        ),
+        avx2:   |y| _mm256_or_si256(
+            _mm256_cmpeq_epi8(y, _mm256_set1_epi8(a as i8)),
+            _mm256_cmpeq_epi8(y, _mm256_set1_epi8(b as i8)),
+        ),
+        neon:   |z| vorrq_u8(vceqq_u8(z, vdupq_n_u8(a)), vceqq_u8(z, vdupq_n_u8(b))),
+    }
+
+    /// position of the first byte not allowed in header values,
+    /// that is other than `\t` and `0x20..=0x7e`
+    ///
+    /// ( x86 has only signed comparisons, where `0x80..` are less than `0x20` )
+    fn find_non_value() {
+        scalar: |b| !matches!(b, b'\t' | 0x20..=0x7e),
+        sse2:   |x| _mm_andnot_si128(
+            _mm_cmpeq_epi8(x, _mm_set1_epi8(b'\t' as i8)),
+            _mm_or_si128(
+                _mm_cmplt_epi8(x, _mm_set1_epi8(0x20)),
+                _mm_cmpeq_epi8(x, _mm_set1_epi8(0x7f)),
+            ),
+        ),
+        avx2:   |y| _mm256_andnot_si256(
+            _mm256_cmpeq_epi8(y, _mm256_set1_epi8(b'\t' as i8)),
+            _mm256_or_si256(
+                _mm256_cmpgt_epi8(_mm256_set1_epi8(0x20), y),
+                _mm256_cmpeq_epi8(y, _mm256_set1_epi8(0x7f)),
+            ),
+        ),
+        neon:   |z| vorrq_u8(
+            vbicq_u8(vcltq_u8(z, vdupq_n_u8(0x20)), vceqq_u8(z, vdupq_n_u8(b'\t'))),
+            vcgeq_u8(z, vdupq_n_u8(0x7f)),
+        ),
+    }
+}
+
+
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// every length up to some vectors, with the target byte at every position
+    fn assert_same_as_scalar(f: impl Fn(&[u8]) -> Option<usize>, scalar: impl Fn(&[u8]) -> Option<usize>, target: u8) {
+        for len in 0..100 {
+            let mut bytes = (0..len).map(|i| b"abcdefghijklmnopqrstuvwxyz-/ 0123"[i % 33]).collect::<Vec<_>>();
+            assert_eq!(f(&bytes), scalar(&bytes), "len {len}");
+            for at in 0..len {
+                let original = std::mem::replace(&mut bytes[at], target);
+                assert_eq!(f(&bytes), scalar(&bytes), "len {len}, at {at}");
+                assert_eq!(f(&bytes[at..]), scalar(&bytes[at..]), "len {len}, from {at}");
+                bytes[at] = original;
+            }
+        }
+    }
+
+    #[test]
+    fn test_find() {
+        for delimiter in [b' ', b':', b'\r', b'?', 0, 0xff] {
+            assert_same_as_scalar(|b| find(b, delimiter), |b| find::scalar(b, delimiter), delimiter);
+        }
+        assert_eq!(find(b"GET /index.html?lang=en HTTP/1.1\r\n", b' '), Some(3));
+        assert_eq!(find(b"Some-Long-Custom-Header-Name-Over-32-Bytes: value", b':'), Some(42));
+        assert_eq!(find(b"no delimiter in bytes longer than a vector", b'\r'), None);
+    }
+
+    #[test]
+    fn test_find2() {
+        for (a, b) in [(b' ', b'?'), (b'?', b' ')] {
+            assert_same_as_scalar(|bytes| find2(bytes, a, b), |bytes| find2::scalar(bytes, a, b), a);
+            assert_same_as_scalar(|bytes| find2(bytes, a, b), |bytes| find2::scalar(bytes, a, b), b);
+        }
+        assert_eq!(find2(b"/api/v2/status?type=library&lang=rust HTTP/1.1", b' ', b'?'), Some(14));
+    }
+
+    #[test]
+    fn test_find_non_value() {
+        for byte in 0..=u8::MAX {
+            assert_same_as_scalar(find_non_value, find_non_value::scalar, byte);
+        }
+        assert_eq!(find_non_value(b"Mozilla/5.0 (platform; rv:geckoversion) Gecko/geckotrail\tFirefox"), None);
+        assert_eq!(find_non_value("text/plain; charset=utf-8; name=\"caf\u{e9}.txt\"".as_bytes()), Some(36));
+    }
+}